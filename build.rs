@@ -0,0 +1,17 @@
+//! Regenerate the C header for the `capi` feature.
+
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).unwrap();
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{dir}/src/capi.rs"))
+            .generate()
+            .expect("failed to generate the C header")
+            .write_to_file(format!("{dir}/include/fw.h"));
+    }
+}
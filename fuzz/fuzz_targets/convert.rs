@@ -0,0 +1,75 @@
+//! Run the whole conversion pipeline over arbitrary bytes with arbitrary options, looking for
+//! panics in the tokenizers and layout. Run with `cargo fuzz run convert`.
+
+#![no_main]
+
+use fw::case::Case;
+use fw::convert::{convert_bytes, Options};
+use fw::format::Format;
+use fw::layout::{Align, BoxChars, Limit, Tabs, Truncate};
+use fw::protect::Protect;
+use fw::style::{Classes, Digits, Style};
+use libfuzzer_sys::fuzz_target;
+
+/// Pick options from the first few bytes of the input, so that the fuzzer can explore them along
+/// with the text. Returns the options and the rest of the input.
+fn options(data: &[u8]) -> Option<(Options, &[u8])> {
+    let (&[flags, more, style, classes, cols, extra], text) = data.split_first_chunk()?;
+    let bit = |byte: u8, n: u8| byte & (1 << n) != 0;
+    let (number, quote) = (bit(cols, 6), bit(cols, 7));
+    let cols = usize::from(cols % 64);
+    let options = Options {
+        style: Style::ALL[usize::from(style) % Style::ALL.len()]
+            .with_seed(u64::from(style))
+            .into(),
+        chaos: bit(extra, 5).then(|| {
+            let styles = vec![Style::Fullwidth, Style::Circled, Style::Braille];
+            (styles, u64::from(more))
+        }),
+        classes: Classes::NAMES
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| bit(classes, i as u8))
+            .fold(Classes::NONE, |all, (_, &(_, class))| all.union(class)),
+        decode: bit(flags, 0),
+        decode_first: bit(flags, 1),
+        skip_converted: bit(flags, 2),
+        emoji: bit(flags, 3),
+        case: bit(extra, 2).then(|| {
+            let name = Case::NAMES[usize::from(extra >> 3) % Case::NAMES.len()];
+            Case::from_name(name, u64::from(extra)).unwrap()
+        }),
+        cjk_punct: bit(flags, 4),
+        leet: bit(flags, 5).then_some(more % 4),
+        digits: bit(extra, 6).then(|| {
+            Digits::from_name(
+                Digits::NAMES[usize::from(extra >> 7) * 2 + usize::from(bit(more, 0))],
+            )
+            .unwrap()
+        }),
+        strip_ansi: bit(flags, 6),
+        strip_invisible: bit(extra, 0),
+        keep_urls: bit(flags, 7),
+        protect: bit(extra, 2)
+            .then(|| Protect::parse("fw\nhello world\n*.r?\n@*"))
+            .unwrap_or_default(),
+        template: bit(more, 2).then_some(bit(more, 3)),
+        bidi_isolate: bit(extra, 1),
+        expand_tabs: bit(more, 4).then_some(Tabs::Stops(cols)),
+        wrap: bit(more, 5).then_some(cols),
+        number,
+        quote,
+        pad: bit(more, 6).then_some((cols, Align::Center)),
+        draw_box: bit(more, 7).then(|| BoxChars::from_spec("light").unwrap()),
+        truncate: bit(more, 1).then(|| (Limit::Width(cols), Truncate::Middle, "…".to_owned())),
+        format: Format::ALL[usize::from(classes >> 4) % Format::ALL.len()],
+    };
+    Some((options, text))
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((options, text)) = options(data) {
+        let out = convert_bytes(text, &options);
+        assert!(std::str::from_utf8(&out).is_ok());
+    }
+});
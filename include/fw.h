@@ -0,0 +1,81 @@
+/*
+ * fw: text styles like fullwidth, for C.
+ *
+ * Strings are NUL-terminated UTF-8. On success each function returns FW_OK and sets *out to a
+ * new string owned by the caller, which must be released with fw_free(). On failure it returns
+ * one of the FW_ERR_ codes and sets *out to NULL.
+ */
+
+#ifndef FW_H
+#define FW_H
+
+/* Generated from src/capi.rs by cbindgen when building with --features capi. Don't edit. */
+
+
+
+/**
+ * Success.
+ */
+#define FW_OK 0
+
+/**
+ * An argument was NULL.
+ */
+#define FW_ERR_NULL -1
+
+/**
+ * An input wasn't valid UTF-8.
+ */
+#define FW_ERR_UTF8 -2
+
+/**
+ * The style name isn't one that fw knows.
+ */
+#define FW_ERR_STYLE -3
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Convert `text` to fullwidth.
+ *
+ * # Safety
+ *
+ * `text` must be a NUL-terminated string, and `out` must be valid to write a pointer to.
+ */
+int fw_to_fullwidth(const char *text, char **out);
+
+/**
+ * Convert fullwidth text back to ASCII.
+ *
+ * # Safety
+ *
+ * As for `fw_to_fullwidth`.
+ */
+int fw_from_fullwidth(const char *text, char **out);
+
+/**
+ * Convert `text` to the style called `style`, like `fw --style`, or decode it back if `decode`
+ * is nonzero.
+ *
+ * # Safety
+ *
+ * As for `fw_to_fullwidth`, and `style` must be a NUL-terminated string too.
+ */
+int fw_apply_style(const char *text, const char *style, int decode, char **out);
+
+/**
+ * Release a string returned by any of the other functions. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `s` must be NULL or a string from this library that hasn't been freed yet.
+ */
+void fw_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FW_H */
@@ -0,0 +1,93 @@
+//! Splitting text into ANSI escape sequences and the visible text between them, so that colored
+//! command output can be converted without breaking its SGR codes.
+
+/// A piece of text from [`tokens`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Text(&'a str),
+    Escape(&'a str),
+}
+
+/// The length in bytes of the escape sequence at the start of `s`, which must start with ESC. An
+/// unterminated sequence runs to the end of `s`.
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let end_at = |i: Option<usize>| i.map_or(bytes.len(), |i| i + 1);
+    match bytes.get(1) {
+        // CSI: parameter and intermediate bytes, then one final byte
+        Some(b'[') => end_at(
+            bytes[2..]
+                .iter()
+                .position(|b| (0x40..=0x7E).contains(b))
+                .map(|i| i + 2),
+        ),
+        // OSC, like hyperlinks and window titles: terminated by BEL or ST (ESC \)
+        Some(b']') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1B if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        // nF sequences like ESC ( B: intermediate bytes, then one final char
+        Some(0x20..=0x2F) => {
+            let i = 1 + bytes[1..]
+                .iter()
+                .take_while(|b| (0x20..=0x2F).contains(*b))
+                .count();
+            i + s[i..].chars().next().map_or(0, char::len_utf8)
+        }
+        // everything else is ESC and a single char
+        Some(_) => 1 + s[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Split `text` into escape sequences and the text between them.
+pub fn tokens(text: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (token, len) = match rest.find('\x1b') {
+            Some(0) => {
+                let len = escape_len(rest);
+                (Token::Escape(&rest[..len]), len)
+            }
+            Some(i) => (Token::Text(&rest[..i]), i),
+            None => (Token::Text(rest), rest.len()),
+        };
+        rest = &rest[len..];
+        Some(token)
+    })
+}
+
+/// Remove every escape sequence from `text`, for `--strip-ansi`.
+pub fn strip(text: &str) -> String {
+    tokens(text)
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            Token::Escape(_) => None,
+        })
+        .collect()
+}
+
+/// Convert the visible text in `text` with `convert`, passing escape sequences through as-is.
+pub fn apply(text: &str, mut convert: impl FnMut(&str) -> String) -> String {
+    if !text.contains('\x1b') {
+        return convert(text);
+    }
+    let mut out = String::with_capacity(text.len() * 3);
+    for token in tokens(text) {
+        match token {
+            Token::Text(text) => out.push_str(&convert(text)),
+            Token::Escape(escape) => out.push_str(escape),
+        }
+    }
+    out
+}
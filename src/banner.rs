@@ -0,0 +1,200 @@
+//! `fw banner`, rendering text as large multi-line letters.
+
+mod block;
+mod dots;
+mod figlet;
+mod tiny;
+
+use std::collections::HashMap;
+use std::fs;
+
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use fw::style::Style;
+
+use crate::{clipboard, input};
+
+/// A bitmap-ish font where every glyph is `height` rows of text.
+pub struct Font {
+    height: usize,
+    glyphs: HashMap<char, Vec<String>>,
+}
+
+impl Font {
+    /// The built-in block font, with `#` replaced by `fill`.
+    pub fn builtin(fill: char) -> Self {
+        Self::from_glyphs(block::HEIGHT, block::GLYPHS, fill)
+    }
+
+    /// The tiny font for `--dots`.
+    pub fn tiny() -> Self {
+        Self::from_glyphs(tiny::HEIGHT, tiny::GLYPHS, '#')
+    }
+
+    fn from_glyphs<const HEIGHT: usize>(
+        height: usize,
+        glyphs: &[(char, [&str; HEIGHT])],
+        fill: char,
+    ) -> Self {
+        let glyphs = glyphs
+            .iter()
+            .map(|(c, rows)| {
+                let rows = rows
+                    .iter()
+                    .map(|row| row.replace('#', fill.encode_utf8(&mut [0; 4])));
+                (*c, rows.collect())
+            })
+            .collect();
+        Self { height, glyphs }
+    }
+
+    /// Load a FIGlet `.flf` font file.
+    pub fn load_figlet(path: &str) -> anyhow::Result<Self> {
+        let data = fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+        figlet::parse(&data).with_context(|| format!("failed to load font {path}"))
+    }
+
+    /// Find the glyph for `c`, falling back to uppercase since the built-in font only has capitals.
+    /// Characters the font doesn't have are skipped.
+    fn glyph(&self, c: char) -> Option<&[String]> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&c.to_ascii_uppercase()))
+            .map(Vec::as_slice)
+    }
+
+    /// Render `text` like [`render`](Self::render), then pack it into braille dots. Each input
+    /// line starts a new row of braille cells.
+    pub fn render_dots(&self, text: &str, spacing: usize) -> String {
+        let lines: Vec<String> = text
+            .split('\n')
+            .map(|line| {
+                let rendered = self.render(line, spacing);
+                dots::pack(&rendered.split('\n').collect::<Vec<_>>())
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    /// Render `text`, with each input line becoming its own row of big letters.
+    pub fn render(&self, text: &str, spacing: usize) -> String {
+        let mut out = String::new();
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let glyphs: Vec<&[String]> = line.chars().filter_map(|c| self.glyph(c)).collect();
+            for row in 0..self.height {
+                if row > 0 {
+                    out.push('\n');
+                }
+                let mut rendered = String::new();
+                for (j, glyph) in glyphs.iter().enumerate() {
+                    if j > 0 {
+                        rendered.extend(std::iter::repeat_n(' ', spacing));
+                    }
+                    // pad ragged rows so that the following glyphs stay lined up
+                    let width = glyph.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+                    let cell = glyph.get(row).map_or("", String::as_str);
+                    rendered.push_str(cell);
+                    rendered.extend(std::iter::repeat_n(' ', width - cell.chars().count()));
+                }
+                out.push_str(rendered.trim_end());
+            }
+        }
+        out
+    }
+}
+
+pub fn command() -> Command {
+    clipboard::add_args(Command::new("banner"))
+        .about("Render text as large banner letters")
+        .arg(
+            Arg::new("font")
+                .short('f')
+                .long("font")
+                .value_name("FILE")
+                .help("Use a FIGlet .flf font instead of the built-in block font"),
+        )
+        .arg(
+            Arg::new("fill")
+                .long("fill")
+                .value_name("CHAR")
+                .value_parser(clap::value_parser!(char))
+                .help(
+                    "Character to draw the built-in font with. Defaults to █, or # when \
+                       a --style is given",
+                ),
+        )
+        .arg(
+            Arg::new("dots")
+                .long("dots")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("fill")
+                .help(
+                    "Draw the letters as dot-matrix pixel art in braille, two by four pixels \
+                       to a character, using a tiny built-in font unless --font is given",
+                ),
+        )
+        .arg(
+            Arg::new("spacing")
+                .long("spacing")
+                .value_name("COLS")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .help("Blank columns between letters"),
+        )
+        .arg(
+            Arg::new("style")
+                .short('s')
+                .long("style")
+                .value_name("STYLE")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    Style::ALL.map(Style::name),
+                ))
+                .help("Convert the rendered banner to a style, e.g. fullwidth"),
+        )
+        .arg(
+            Arg::new("text")
+                .action(ArgAction::Append)
+                .help("Text to render. Arguments will be joined with spaces. Omit to read stdin."),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let text = match args.get_many::<String>("text") {
+        Some(words) => words.map(String::as_str).collect::<Vec<_>>().join(" "),
+        None => input::read_stdin(false)?,
+    };
+    let style = args
+        .get_one::<String>("style")
+        .map(|name| Style::from_name(name).unwrap());
+
+    let dots = args.get_flag("dots");
+    let font = match args.get_one::<String>("font") {
+        Some(path) => Font::load_figlet(path)?,
+        None if dots => Font::tiny(),
+        None => {
+            // a block character stays halfwidth when converted, which would misalign the
+            // (converted) blank cells around it
+            let default_fill = if style.is_some() { '#' } else { '█' };
+            Font::builtin(
+                args.get_one::<char>("fill")
+                    .copied()
+                    .unwrap_or(default_fill),
+            )
+        }
+    };
+
+    let spacing = *args.get_one::<usize>("spacing").unwrap();
+    let mut banner = if dots {
+        font.render_dots(&text, spacing)
+    } else {
+        font.render(&text, spacing)
+    };
+    if let Some(style) = style {
+        banner = style.apply(&banner);
+    }
+    println!("{banner}");
+    clipboard::copy(args, &banner)
+}
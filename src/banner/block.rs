@@ -0,0 +1,62 @@
+//! The built-in block font: five rows, with `#` for filled cells.
+
+pub const HEIGHT: usize = 5;
+
+#[rustfmt::skip]
+pub const GLYPHS: &[(char, [&str; HEIGHT])] = &[
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('A', [" ### ", "#   #", "#####", "#   #", "#   #"]),
+    ('B', ["#### ", "#   #", "#### ", "#   #", "#### "]),
+    ('C', [" ####", "#    ", "#    ", "#    ", " ####"]),
+    ('D', ["#### ", "#   #", "#   #", "#   #", "#### "]),
+    ('E', ["#####", "#    ", "#### ", "#    ", "#####"]),
+    ('F', ["#####", "#    ", "#### ", "#    ", "#    "]),
+    ('G', [" ####", "#    ", "#  ##", "#   #", " ####"]),
+    ('H', ["#   #", "#   #", "#####", "#   #", "#   #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  ###", "    #", "    #", "#   #", " ### "]),
+    ('K', ["#   #", "#  # ", "###  ", "#  # ", "#   #"]),
+    ('L', ["#    ", "#    ", "#    ", "#    ", "#####"]),
+    ('M', ["#   #", "## ##", "# # #", "#   #", "#   #"]),
+    ('N', ["#   #", "##  #", "# # #", "#  ##", "#   #"]),
+    ('O', [" ### ", "#   #", "#   #", "#   #", " ### "]),
+    ('P', ["#### ", "#   #", "#### ", "#    ", "#    "]),
+    ('Q', [" ### ", "#   #", "# # #", "#  # ", " ## #"]),
+    ('R', ["#### ", "#   #", "#### ", "#  # ", "#   #"]),
+    ('S', [" ####", "#    ", " ### ", "    #", "#### "]),
+    ('T', ["#####", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('U', ["#   #", "#   #", "#   #", "#   #", " ### "]),
+    ('V', ["#   #", "#   #", "#   #", " # # ", "  #  "]),
+    ('W', ["#   #", "#   #", "# # #", "## ##", "#   #"]),
+    ('X', ["#   #", " # # ", "  #  ", " # # ", "#   #"]),
+    ('Y', ["#   #", " # # ", "  #  ", "  #  ", "  #  "]),
+    ('Z', ["#####", "   # ", "  #  ", " #   ", "#####"]),
+    ('0', [" ### ", "#  ##", "# # #", "##  #", " ### "]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', [" ### ", "#   #", "  ## ", " #   ", "#####"]),
+    ('3', ["#### ", "    #", " ### ", "    #", "#### "]),
+    ('4', ["#   #", "#   #", "#####", "    #", "    #"]),
+    ('5', ["#####", "#    ", "#### ", "    #", "#### "]),
+    ('6', [" ### ", "#    ", "#### ", "#   #", " ### "]),
+    ('7', ["#####", "    #", "   # ", "  #  ", "  #  "]),
+    ('8', [" ### ", "#   #", " ### ", "#   #", " ### "]),
+    ('9', [" ### ", "#   #", " ####", "    #", " ### "]),
+    ('!', ["#", "#", "#", " ", "#"]),
+    ('?', [" ### ", "#   #", "  ## ", "     ", "  #  "]),
+    ('.', [" ", " ", " ", " ", "#"]),
+    (',', ["  ", "  ", "  ", " #", "# "]),
+    (':', [" ", "#", " ", "#", " "]),
+    ('\'', ["#", "#", " ", " ", " "]),
+    ('"', ["# #", "# #", "   ", "   ", "   "]),
+    ('-', ["    ", "    ", "####", "    ", "    "]),
+    ('+', ["     ", "  #  ", "#####", "  #  ", "     "]),
+    ('=', ["    ", "####", "    ", "####", "    "]),
+    ('/', ["    #", "   # ", "  #  ", " #   ", "#    "]),
+    ('(', [" #", "# ", "# ", "# ", " #"]),
+    (')', ["# ", " #", " #", " #", "# "]),
+    ('<', ["   #", "  # ", " #  ", "  # ", "   #"]),
+    ('>', ["#   ", " #  ", "  # ", " #  ", "#   "]),
+    ('#', [" # # ", "#####", " # # ", "#####", " # # "]),
+    ('*', ["     ", "# # #", " ### ", "# # #", "     "]),
+    ('_', ["    ", "    ", "    ", "    ", "####"]),
+];
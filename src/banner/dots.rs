@@ -0,0 +1,47 @@
+//! Drawing banners with braille dots, for `fw banner --dots`.
+//!
+//! Each braille cell is a grid of two by four dots, so packing the filled cells of a rendered
+//! banner into braille makes letters a quarter of the size.
+
+/// The blank braille pattern, which the dots are added to.
+const BLANK: u32 = 0x2800;
+
+/// The bit for the dot in each row and column of a braille cell. The first six dots go down the
+/// left column then the right, and dots 7 and 8 were added below them later.
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Pack the rows of a rendered banner into braille, with every character other than a space
+/// being a filled dot. Rows can be ragged, and the last cells are padded out with blank dots.
+pub fn pack(rows: &[&str]) -> String {
+    let pixels: Vec<Vec<bool>> = rows
+        .iter()
+        .map(|row| row.chars().map(|c| c != ' ').collect())
+        .collect();
+    let width = pixels.iter().map(Vec::len).max().unwrap_or(0);
+    let filled = |row: usize, col: usize| {
+        pixels
+            .get(row)
+            .and_then(|r| r.get(col))
+            .copied()
+            .unwrap_or(false)
+    };
+
+    let mut out = String::new();
+    for cell_row in 0..pixels.len().div_ceil(4) {
+        if cell_row > 0 {
+            out.push('\n');
+        }
+        for cell_col in 0..width.div_ceil(2) {
+            let mut pattern = BLANK;
+            for (dy, bits) in DOTS.iter().enumerate() {
+                for (dx, bit) in bits.iter().enumerate() {
+                    if filled(cell_row * 4 + dy, cell_col * 2 + dx) {
+                        pattern |= bit;
+                    }
+                }
+            }
+            out.push(char::from_u32(pattern).unwrap());
+        }
+    }
+    out
+}
@@ -0,0 +1,101 @@
+//! Loading FIGlet `.flf` fonts.
+//!
+//! See figfont.txt from the FIGlet distribution for the format. We only need the glyphs
+//! themselves, since rendering always uses full-width layout (no kerning or smushing).
+
+use std::collections::HashMap;
+use std::str::Lines;
+
+use anyhow::{anyhow, bail, Context};
+
+use super::Font;
+
+/// The characters every FIGlet font must define, in order: printable ASCII then the seven
+/// "Deutsch" characters.
+fn required_chars() -> impl Iterator<Item = char> {
+    (' '..='~').chain(['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'])
+}
+
+/// Parse a code-tagged character's code, which may be decimal, `0x` hex, or `0` octal.
+fn parse_code(s: &str) -> Option<i64> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let value = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if s.len() > 1 && s.starts_with('0') {
+        i64::from_str_radix(&s[1..], 8).ok()?
+    } else {
+        s.parse().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Read one glyph's `height` rows. `what` describes the glyph for error messages.
+fn read_glyph(
+    lines: &mut Lines,
+    height: usize,
+    hardblank: char,
+    what: impl Fn() -> String,
+) -> anyhow::Result<Vec<String>> {
+    let mut rows = Vec::with_capacity(height);
+    for _ in 0..height {
+        let line = lines
+            .next()
+            .with_context(|| format!("font ended in the glyph for {}", what()))?;
+        // every row ends with one or more endmark characters, usually '@'
+        let endmark = line.chars().next_back().unwrap_or(' ');
+        rows.push(line.trim_end_matches(endmark).replace(hardblank, " "));
+    }
+    Ok(rows)
+}
+
+pub fn parse(data: &str) -> anyhow::Result<Font> {
+    let mut lines = data.lines();
+    let header = lines.next().context("empty font file")?;
+    let Some(params) = header.strip_prefix("flf2a") else {
+        bail!("not a FIGlet font (missing flf2a signature)");
+    };
+    let mut params = params.chars();
+    let hardblank = params.next().context("missing hardblank in font header")?;
+    let fields: Vec<&str> = params.as_str().split_whitespace().collect();
+    let field = |i: usize, name: &str| -> anyhow::Result<usize> {
+        fields
+            .get(i)
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| anyhow!("invalid {name} in font header"))
+    };
+    let height = field(0, "height")?;
+    let comment_lines = field(4, "comment line count")?;
+    if height == 0 {
+        bail!("font height is zero");
+    }
+
+    for _ in 0..comment_lines {
+        lines.next().context("font ended in the header comment")?;
+    }
+
+    let mut glyphs = HashMap::new();
+    for c in required_chars() {
+        let rows = read_glyph(&mut lines, height, hardblank, || format!("{c:?}"))?;
+        glyphs.insert(c, rows);
+    }
+
+    // optional code-tagged characters follow until the end of the file
+    while let Some(tag) = lines.next() {
+        if tag.trim().is_empty() {
+            continue;
+        }
+        let code_str = tag.split_whitespace().next().unwrap_or("");
+        let code = parse_code(code_str)
+            .with_context(|| format!("invalid code-tagged character '{code_str}'"))?;
+        let rows = read_glyph(&mut lines, height, hardblank, || format!("code {code}"))?;
+        // negative codes are reserved for the font's own use and can't be typed
+        if let Some(c) = u32::try_from(code).ok().and_then(char::from_u32) {
+            glyphs.insert(c, rows);
+        }
+    }
+
+    Ok(Font { height, glyphs })
+}
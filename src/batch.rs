@@ -0,0 +1,109 @@
+//! `fw batch`, converting a file of messages one per line in a single run.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Arg, ArgMatches, Command};
+use fw::format::Format;
+
+use crate::{cli, input};
+
+pub fn command() -> Command {
+    Command::new("batch")
+        .about("Convert a list of messages, one per line")
+        .long_about(
+            "Convert a list of messages, one per line, writing either the converted lines or a \
+             mapping from each message to its conversion. This is much faster than running fw \
+             once per message.",
+        )
+        .arg(cli::style_arg())
+        .arg(cli::seed_arg())
+        .arg(cli::leet_level_arg())
+        .arg(cli::morse_symbols_arg())
+        .arg(
+            Arg::new("input")
+                .short('i')
+                .long("input")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Read the messages from FILE instead of stdin"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Write to FILE instead of stdout"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["lines", "tsv", "json"])
+                .default_value("lines")
+                .help(
+                    "What to write: 'lines' has each converted message on the line of the \
+                       original, 'tsv' has the original and converted message on each line \
+                       with tabs and backslashes escaped as \\t and \\\\, and 'json' is an \
+                       object with the originals as keys. The mappings skip blank lines and \
+                       repeated messages",
+                ),
+        )
+}
+
+/// Escape `text` for a TSV field.
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t")
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let input = match args.get_one::<PathBuf>("input") {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read '{}'", path.display()))?,
+        None => input::read_stdin(true)?,
+    };
+    let style = cli::style_from_args(args);
+    let format = args.get_one::<String>("format").unwrap();
+
+    let mut out: BufWriter<Box<dyn Write>> =
+        BufWriter::new(match args.get_one::<PathBuf>("output") {
+            Some(path) => Box::new(
+                File::create(path)
+                    .with_context(|| format!("failed to create '{}'", path.display()))?,
+            ),
+            None => Box::new(io::stdout().lock()),
+        });
+
+    let mut seen = HashSet::new();
+    let mappings = input
+        .lines()
+        .filter(|line| format == "lines" || (!line.is_empty() && seen.insert(*line)));
+    let result = (|| {
+        if format == "json" {
+            out.write_all(b"{")?;
+        }
+        for (i, line) in mappings.enumerate() {
+            let converted = style.apply(line);
+            match format.as_str() {
+                "tsv" => writeln!(out, "{}\t{}", tsv_field(line), tsv_field(&converted))?,
+                "json" => write!(
+                    out,
+                    "{}\n  {}: {}",
+                    if i > 0 { "," } else { "" },
+                    Format::JsonString.apply(line),
+                    Format::JsonString.apply(&converted)
+                )?,
+                _ => writeln!(out, "{converted}")?,
+            }
+        }
+        if format == "json" {
+            out.write_all(b"\n}\n")?;
+        }
+        out.flush()
+    })();
+    result.context("failed to write output")
+}
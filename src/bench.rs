@@ -0,0 +1,192 @@
+//! `fw bench`, measuring conversion throughput over generated text.
+//!
+//! This is for packagers and anyone changing the hot paths, so it's built only with the `bench`
+//! feature. Each benchmark is run a few times to warm up and then sampled, and the report gives
+//! the fastest, median, and slowest sample like criterion does.
+
+use std::io::Write;
+use std::process::{Command as Process, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgMatches, Command};
+use fw::style::{Classes, Style};
+
+/// Where to find ourselves for the streaming benchmarks, see [`crate::clipboard`].
+const SELF_EXE: &str = "/proc/self/exe";
+
+const SIZES: [(&str, usize); 3] = [("1KiB", 1024), ("64KiB", 64 * 1024), ("1MiB", 1024 * 1024)];
+
+/// The kinds of text to generate, as pools of clusters to draw from. Plain ASCII takes the fast
+/// paths, and the others go through grapheme segmentation.
+const MIXES: [(&str, &[&str]); 3] = [
+    (
+        "ascii",
+        &[
+            "the", "quick", "brown", "fox", "cate", "meme", "42", "1337", ",", ".", "!", "?", "(",
+            ")", "'", "\"", "-",
+        ],
+    ),
+    (
+        "mixed",
+        &[
+            "cate",
+            "meme",
+            "e\u{301}",
+            "ñ",
+            "漢字",
+            "かな",
+            "🐱",
+            "👨\u{200D}👩\u{200D}👧",
+            "🇯🇵",
+            "1\u{FE0F}\u{20E3}",
+            "ｃａｔｅ",
+            "!",
+            ",",
+        ],
+    ),
+    (
+        "kana",
+        &["ｶ", "ｶﾞ", "ﾊﾟ", "ｱ", "ｲ", "ｳ", "ｴ", "ｵ", "ｰ", "｡", "ｷﾞ", "ﾝ"],
+    ),
+];
+
+pub fn command() -> Command {
+    Command::new("bench")
+        .about("Measure how fast text converts")
+        .long_about(
+            "Measure how fast text converts, over generated text of different sizes and \
+             mixes of characters. 'apply' benchmarks convert in memory with each style, and \
+             'stream' ones pipe the text through a copy of fw reading stdin.",
+        )
+        .arg(
+            Arg::new("filter")
+                .value_name("FILTER")
+                .help("Only run benchmarks whose name contains FILTER, e.g. apply/fullwidth"),
+        )
+        .arg(
+            Arg::new("samples")
+                .long("samples")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .default_value("20")
+                .help("How many times to time each benchmark"),
+        )
+}
+
+/// A tiny deterministic random number generator (xorshift64), so that every run benchmarks the
+/// same text.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+/// Generate about `size` bytes of text from `pool`, as words separated by spaces with a line
+/// break every so often.
+fn corpus(pool: &[&str], size: usize) -> String {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let mut text = String::with_capacity(size + 64);
+    let mut line_len = 0;
+    while text.len() < size {
+        let word = pool[rng.below(pool.len())];
+        text.push_str(word);
+        line_len += word.len();
+        if line_len > 60 {
+            text.push('\n');
+            line_len = 0;
+        } else {
+            text.push(' ');
+        }
+    }
+    text
+}
+
+/// Time `f` `samples` times after a few warm-up runs, and print one line of report for `bytes`
+/// of input.
+fn measure(
+    name: &str,
+    bytes: usize,
+    samples: u32,
+    mut f: impl FnMut() -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let warm_up = Instant::now();
+    while warm_up.elapsed() < Duration::from_millis(100) {
+        f()?;
+    }
+    let mut times = Vec::with_capacity(samples as usize);
+    for _ in 0..samples {
+        let start = Instant::now();
+        f()?;
+        times.push(start.elapsed());
+    }
+    times.sort_unstable();
+    let median = times[times.len() / 2];
+    let throughput = bytes as f64 / (1024.0 * 1024.0) / median.as_secs_f64();
+    println!(
+        "{name:<32} time: [{:>10.3?} {:>10.3?} {:>10.3?}]  thrpt: {throughput:>8.1} MiB/s",
+        times[0],
+        median,
+        times[times.len() - 1],
+    );
+    Ok(())
+}
+
+/// Pipe `text` through a fresh fw with `args`, throwing away the output.
+fn stream(text: &str, args: &[&str]) -> anyhow::Result<()> {
+    let mut child = Process::new(SELF_EXE)
+        .arg("--no-clipboard")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("failed to run fw")?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(text.as_bytes())
+        .context("failed to write to fw")?;
+    if !child.wait().context("failed to wait for fw")?.success() {
+        bail!("fw {} failed", args.join(" "));
+    }
+    Ok(())
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let filter = args.get_one::<String>("filter").map_or("", String::as_str);
+    let samples = *args.get_one::<u32>("samples").unwrap();
+
+    let mut out = String::new();
+    for (mix, pool) in MIXES {
+        for (size_name, size) in SIZES {
+            let text = corpus(pool, size);
+            for style in Style::ALL {
+                let name = format!("apply/{}/{mix}/{size_name}", style.name());
+                if !name.contains(filter) {
+                    continue;
+                }
+                measure(&name, text.len(), samples, || {
+                    out.clear();
+                    style.apply_to(&text, Classes::ALL, &mut out);
+                    Ok(())
+                })?;
+            }
+            for jobs in ["1", "4"] {
+                let name = format!("stream/j{jobs}/{mix}/{size_name}");
+                if !name.contains(filter) {
+                    continue;
+                }
+                measure(&name, text.len(), samples, || {
+                    stream(&text, &["--jobs", jobs])
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Keeping right-to-left text in order, for `--bidi`.
+//!
+//! Converting turns spaces into wide spaces and ASCII punctuation into fullwidth punctuation,
+//! which the bidirectional algorithm treats differently, so a Hebrew or Arabic phrase in the
+//! middle of converted text can come out with its words or the text around it reordered.
+//! Wrapping each right-to-left run in an isolate stops it from affecting anything around it.
+
+use unicode_bidi::BidiInfo;
+
+/// FIRST STRONG ISOLATE, which starts an isolate in the direction of its first letter.
+pub const FSI: char = '\u{2068}';
+/// POP DIRECTIONAL ISOLATE, which ends one.
+pub const PDI: char = '\u{2069}';
+
+/// Whether `text` has anything written right to left.
+pub fn has_rtl(text: &str) -> bool {
+    BidiInfo::new(text, None).has_rtl()
+}
+
+/// Wrap each run of right-to-left text in `text` in [`FSI`] and [`PDI`]. Paragraphs that are
+/// right to left as a whole are left as they are, since there's nothing around them to protect.
+pub fn isolate(text: &str) -> String {
+    let info = BidiInfo::new(text, None);
+    if !info.has_rtl() {
+        return text.to_owned();
+    }
+    let mut out = String::with_capacity(text.len() + 16);
+    for para in &info.paragraphs {
+        let mut in_run = false;
+        for (i, c) in text[para.range.clone()].char_indices() {
+            let embedded = info.levels[para.range.start + i] > para.level && !para.level.is_rtl();
+            // keep the run's trailing line break outside of it
+            let embedded = embedded && c != '\n' && c != '\r';
+            if embedded != in_run {
+                out.push(if embedded { FSI } else { PDI });
+                in_run = embedded;
+            }
+            out.push(c);
+        }
+        if in_run {
+            out.push(PDI);
+        }
+    }
+    out
+}
@@ -0,0 +1,110 @@
+//! C bindings for the conversion core, so that C programs can use the same tables without
+//! running the `fw` binary. The header is `include/fw.h`, regenerated by the build script.
+//!
+//! Every function takes NUL-terminated UTF-8 and, on success, sets `*out` to a new string that
+//! the caller owns and must release with [`fw_free`]. On failure `*out` is set to NULL.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::style::Style;
+
+/// Success.
+pub const FW_OK: c_int = 0;
+/// An argument was NULL.
+pub const FW_ERR_NULL: c_int = -1;
+/// An input wasn't valid UTF-8.
+pub const FW_ERR_UTF8: c_int = -2;
+/// The style name isn't one that fw knows.
+pub const FW_ERR_STYLE: c_int = -3;
+
+/// Borrow `s` as a `&str`.
+///
+/// # Safety
+///
+/// `s` must be NULL or point to a NUL-terminated string that outlives the result.
+unsafe fn borrow<'a>(s: *const c_char) -> Result<&'a str, c_int> {
+    if s.is_null() {
+        return Err(FW_ERR_NULL);
+    }
+    CStr::from_ptr(s).to_str().map_err(|_| FW_ERR_UTF8)
+}
+
+/// Run `f` on `text` and hand the result to the caller through `out`.
+///
+/// # Safety
+///
+/// As for the exported functions.
+unsafe fn convert(
+    text: *const c_char,
+    out: *mut *mut c_char,
+    f: impl FnOnce(&str) -> Result<String, c_int>,
+) -> c_int {
+    if out.is_null() {
+        return FW_ERR_NULL;
+    }
+    *out = std::ptr::null_mut();
+    let result = borrow(text).and_then(f);
+    match result {
+        Ok(converted) => {
+            // the input had no NULs and no style adds one
+            *out = CString::new(converted).unwrap().into_raw();
+            FW_OK
+        }
+        Err(err) => err,
+    }
+}
+
+/// Convert `text` to fullwidth.
+///
+/// # Safety
+///
+/// `text` must be a NUL-terminated string, and `out` must be valid to write a pointer to.
+#[no_mangle]
+pub unsafe extern "C" fn fw_to_fullwidth(text: *const c_char, out: *mut *mut c_char) -> c_int {
+    convert(text, out, |text| Ok(Style::Fullwidth.apply(text)))
+}
+
+/// Convert fullwidth text back to ASCII.
+///
+/// # Safety
+///
+/// As for `fw_to_fullwidth`.
+#[no_mangle]
+pub unsafe extern "C" fn fw_from_fullwidth(text: *const c_char, out: *mut *mut c_char) -> c_int {
+    convert(text, out, |text| Ok(Style::Fullwidth.decode(text)))
+}
+
+/// Convert `text` to the style called `style`, like `fw --style`, or decode it back if `decode`
+/// is nonzero.
+///
+/// # Safety
+///
+/// As for `fw_to_fullwidth`, and `style` must be a NUL-terminated string too.
+#[no_mangle]
+pub unsafe extern "C" fn fw_apply_style(
+    text: *const c_char,
+    style: *const c_char,
+    decode: c_int,
+    out: *mut *mut c_char,
+) -> c_int {
+    convert(text, out, |text| {
+        let style = Style::from_name(borrow(style)?).ok_or(FW_ERR_STYLE)?;
+        Ok(if decode != 0 {
+            style.decode(text)
+        } else {
+            style.apply(text)
+        })
+    })
+}
+
+/// Release a string returned by any of the other functions. NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string from this library that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fw_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
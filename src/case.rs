@@ -0,0 +1,80 @@
+//! Changing the case of text before it's converted, for `--case`.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::ansi;
+use crate::style::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Upper,
+    Lower,
+    /// The first letter of each word in upper case and the rest in lower case.
+    Title,
+    /// Each letter in upper or lower case at random, the same way every time for the same seed.
+    Random {
+        seed: u64,
+    },
+}
+
+impl Case {
+    pub const NAMES: [&'static str; 4] = ["upper", "lower", "title", "random"];
+
+    /// The case called `name`, making random choices from `seed`.
+    pub fn from_name(name: &str, seed: u64) -> Option<Self> {
+        match name {
+            "upper" => Some(Case::Upper),
+            "lower" => Some(Case::Lower),
+            "title" => Some(Case::Title),
+            "random" => Some(Case::Random { seed }),
+            _ => None,
+        }
+    }
+
+    /// Change the case of the visible text in `text`, leaving ANSI escapes alone.
+    pub fn apply(mut self, text: &str) -> String {
+        self.apply_mut(text)
+    }
+
+    /// Like [`apply`](Self::apply), but carry the random choices on from one call to the next,
+    /// so that changing text in parts picks the same as changing it all at once.
+    pub fn apply_mut(&mut self, text: &str) -> String {
+        ansi::apply(text, |text| match self {
+            Case::Upper => text.to_uppercase(),
+            Case::Lower => text.to_lowercase(),
+            Case::Title => title(text),
+            Case::Random { seed } => random(text, seed),
+        })
+    }
+}
+
+fn title(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for word in text.split_word_bounds() {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) if first.is_alphanumeric() => {
+                out.extend(first.to_uppercase());
+                // lowercasing the rest as a whole gets the final sigma right
+                out.push_str(&chars.as_str().to_lowercase());
+            }
+            _ => out.push_str(word),
+        }
+    }
+    out
+}
+
+/// Change the case of `text` at random, leaving `seed` ready for the text after it.
+fn random(text: &str, seed: &mut u64) -> String {
+    let mut rng = Rng::new(*seed);
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if rng.next() & 1 == 0 {
+            out.extend(c.to_lowercase());
+        } else {
+            out.extend(c.to_uppercase());
+        }
+    }
+    *seed = rng.into_seed();
+    out
+}
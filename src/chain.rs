@@ -0,0 +1,79 @@
+//! Styles defined in the config file as a chain of other styles, see `--style`.
+//!
+//! ```toml
+//! [styles.meme]
+//! chain = ["fullwidth", "spaced"]
+//! suffix = " ✨"
+//! ```
+//!
+//! A chain can include other config styles, which are applied with their own prefix and suffix
+//! before the rest of the chain converts the result. Built-in styles can't be redefined.
+
+use anyhow::bail;
+use fw::style::{Chain, Step, Style};
+
+use crate::config::Config;
+
+/// Look up the style called `name`, following config styles that include other config styles
+/// down to the built-in ones.
+pub fn find(config: &Config, name: &str) -> anyhow::Result<Chain> {
+    let mut steps = Vec::new();
+    resolve(config, name, &mut Vec::new(), &mut steps)?;
+    Ok(Chain::new(steps))
+}
+
+/// Add the steps for the style called `name` to `steps`. `path` is the config styles that
+/// include this one, to catch ones that include themselves.
+fn resolve(
+    config: &Config,
+    name: &str,
+    path: &mut Vec<String>,
+    steps: &mut Vec<Step>,
+) -> anyhow::Result<()> {
+    if let Some(style) = Style::from_name(name) {
+        steps.push(Step::Style(style));
+        return Ok(());
+    }
+    let Some(user) = config.styles.get(name) else {
+        match path.last() {
+            Some(parent) => bail!("style '{parent}' includes unknown style '{name}'"),
+            None => bail!(
+                "unknown style '{name}' (available: {})",
+                names(config).join(", ")
+            ),
+        }
+    };
+    if path.iter().any(|n| n == name) {
+        bail!(
+            "style '{name}' includes itself: {} -> {name}",
+            path.join(" -> ")
+        );
+    }
+    if user.chain.is_empty() {
+        bail!("style '{name}' has an empty chain");
+    }
+    path.push(name.to_owned());
+    for next in &user.chain {
+        resolve(config, next, path, steps)?;
+    }
+    path.pop();
+    if !user.prefix.is_empty() || !user.suffix.is_empty() {
+        steps.push(Step::Wrap {
+            prefix: user.prefix.clone(),
+            suffix: user.suffix.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// The names of every style, built-in ones first.
+pub fn names(config: &Config) -> Vec<&str> {
+    let mut user: Vec<&str> = config
+        .styles
+        .keys()
+        .map(String::as_str)
+        .filter(|name| Style::from_name(name).is_none())
+        .collect();
+    user.sort_unstable();
+    Style::ALL.iter().map(|s| s.name()).chain(user).collect()
+}
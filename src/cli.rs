@@ -0,0 +1,112 @@
+//! Command line argument helpers shared by several commands.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::builder::PossibleValuesParser;
+use clap::{value_parser, Arg, ArgMatches};
+use fw::style::{MorseSymbols, Style};
+
+/// The `--style` argument, shared by every command that converts text.
+pub fn style_arg() -> Arg {
+    Arg::new("style")
+        .short('s')
+        .long("style")
+        .value_name("STYLE")
+        .value_parser(PossibleValuesParser::new(Style::ALL.map(Style::name)))
+        .default_value("fullwidth")
+        .env("FW_STYLE")
+        .help("Which style to convert the text to")
+}
+
+/// The `--seed` argument, for styles that make random choices.
+pub fn seed_arg() -> Arg {
+    Arg::new("seed")
+        .long("seed")
+        .value_name("N")
+        .value_parser(value_parser!(u64))
+        .help(
+            "Make the random choices of styles like homoglyph and of --case random from N, \
+               so that the same N always gives the same output",
+        )
+}
+
+/// The `--seed` if it was given, or else one from the clock, for choices that should be random
+/// by default.
+pub fn seed_or_clock(args: &ArgMatches) -> u64 {
+    match args.try_get_one::<u64>("seed") {
+        Ok(Some(&seed)) => seed,
+        _ => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64),
+    }
+}
+
+/// The `--leet-level` argument, for `--style leet` and `--leet`.
+pub fn leet_level_arg() -> Arg {
+    Arg::new("leet-level")
+        .long("leet-level")
+        .value_name("LEVEL")
+        .value_parser(value_parser!(u8).range(1..=3))
+        .default_value("2")
+        .help(
+            "How much leet to speak, from 1 (only vowels) to 3 (as many letters as have a \
+               replacement)",
+        )
+}
+
+/// The `--morse-symbols` argument, for `--style morse`.
+pub fn morse_symbols_arg() -> Arg {
+    Arg::new("morse-symbols")
+        .long("morse-symbols")
+        .value_name("SYMBOLS")
+        .value_parser(MorseSymbols::parse)
+        .help(
+            "What to write morse code with: ascii (.-/ and spaces, the default), fullwidth \
+               (．－／ and wide spaces), or the dot, dash, word separator, and letter \
+               separator as up to four characters, e.g. '·−'",
+        )
+}
+
+/// Parse a comma-separated list of built-in styles that can be [mixed](fw::style::mix).
+pub fn parse_mixable_styles(list: &str) -> Result<Vec<Style>, String> {
+    list.split(',')
+        .map(|name| match Style::from_name(name.trim()) {
+            Some(style) if style.maps_chars() => Ok(style),
+            Some(_) => Err(format!(
+                "the {name} style can't be mixed, it doesn't convert one character at a time"
+            )),
+            None => {
+                let names: Vec<&str> = Style::ALL
+                    .iter()
+                    .filter(|s| s.maps_chars())
+                    .map(|s| s.name())
+                    .collect();
+                Err(format!(
+                    "unknown style '{name}', expected {}",
+                    names.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Get the style selected by [`style_arg`], with [`style_args`] applied.
+pub fn style_from_args(args: &ArgMatches) -> Style {
+    let style = Style::from_name(args.get_one::<String>("style").unwrap()).unwrap();
+    style_args(args, style)
+}
+
+/// Change `style` according to [`seed_arg`], [`leet_level_arg`], and [`morse_symbols_arg`] if
+/// the command has them.
+pub fn style_args(args: &ArgMatches, mut style: Style) -> Style {
+    if let Ok(Some(&seed)) = args.try_get_one::<u64>("seed") {
+        style = style.with_seed(seed);
+    }
+    if let Ok(Some(&level)) = args.try_get_one::<u8>("leet-level") {
+        style = style.with_level(level);
+    }
+    if let Ok(Some(&symbols)) = args.try_get_one::<MorseSymbols>("morse-symbols") {
+        style = style.with_morse_symbols(symbols);
+    }
+    style
+}
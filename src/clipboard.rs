@@ -0,0 +1,606 @@
+//! Setting the clipboard contents, either ourselves or through a clipboard manager.
+
+use std::cell::OnceCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command as Process, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use arboard::Clipboard;
+use clap::builder::FalseyValueParser;
+use clap::parser::ValueSource;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use crate::holder::{self, Contents};
+use crate::retry::retry;
+use crate::{detect, env_is_nonempty, log, manager, paths, selection};
+
+/// How long to wait before setting the clipboard again when `--verify` fails, doubled for each
+/// further attempt.
+const VERIFY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// How long `--verify` waits for the clipboard to settle before giving up on an attempt.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitMode {
+    NoWait,
+    Foreground,
+    Background,
+    /// Like Background, but fully detached from the terminal and logging to a file.
+    Daemon,
+}
+
+impl WaitMode {
+    /// The name `--wait-mode` knows this mode by.
+    pub fn name(self) -> &'static str {
+        match self {
+            WaitMode::NoWait => "no",
+            WaitMode::Foreground => "foreground",
+            WaitMode::Background => "background",
+            WaitMode::Daemon => "daemon",
+        }
+    }
+}
+
+/// The hidden argument that runs fw as a background holder, see [`spawn_holder`].
+pub const HOLDER_ARG: &str = "__clipboard-holder";
+
+/// Where to find ourselves for [`spawn_holder`]. This still works if the binary was replaced or
+/// deleted since we started, unlike the path it was run from.
+const SELF_EXE: &str = "/proc/self/exe";
+
+fn set_clipboard(
+    contents: &Contents,
+    wait: WaitMode,
+    options: holder::Options,
+) -> anyhow::Result<()> {
+    match wait {
+        WaitMode::NoWait => {
+            // arboard hands the text over to the clipboard manager when it exits
+            let mut cb = Clipboard::new().context("failed to init clipboard")?;
+            cb.set_text(contents.text.as_str())
+                .context("failed to set clipboard contents")
+        }
+        // we're going to be around to serve paste requests, so we can offer every target
+        WaitMode::Foreground => holder::hold(contents.clone(), options),
+        WaitMode::Background | WaitMode::Daemon => spawn_holder(contents, options),
+    }
+}
+
+/// Start another fw with [`HOLDER_ARG`] to hold the clipboard in the background, and leave it
+/// running on its own.
+///
+/// Running a fresh copy of ourselves rather than just forking means it doesn't matter what
+/// threads this process has going, and the holder can be started by hand to debug it. The
+/// contents are written to its stdin, see [`run_holder`].
+fn spawn_holder(contents: &Contents, options: holder::Options) -> anyhow::Result<()> {
+    let mut cmd = Process::new(SELF_EXE);
+    cmd.arg0("fw")
+        .arg(format!("--{HOLDER_ARG}={}", contents.text.len()))
+        .stdin(Stdio::piped())
+        // we may be in a pipeline that shouldn't wait for the holder to exit
+        .stdout(Stdio::null());
+    if let Some(timeout) = options.clear_after {
+        cmd.arg("--clear-after").arg(timeout.as_secs().to_string());
+    }
+    if options.restore {
+        cmd.arg("--restore");
+    }
+    if let Some(max) = options.max_pastes {
+        cmd.arg("--max-paste-count").arg(max.to_string());
+    }
+    log::pass_on(&mut cmd);
+    if options.daemon {
+        // A new session without a controlling terminal, logging to a file rather than whatever
+        // terminal we're in. The second fork of the classic recipe only stops a daemon from
+        // getting a controlling terminal back by opening one, which the holder never does.
+        let log = open_daemon_log()?;
+        cmd.arg("--daemonize")
+            .current_dir("/")
+            .stdout(log.try_clone().context("failed to open log file")?)
+            .stderr(log);
+        // SAFETY: setsid is async-signal-safe
+        unsafe {
+            cmd.pre_exec(|| match libc::setsid() {
+                -1 => Err(io::Error::last_os_error()),
+                _ => Ok(()),
+            });
+        }
+    }
+
+    let mut child = cmd
+        .spawn()
+        .context("failed to start the clipboard holder")?;
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(contents.text.as_bytes())
+        .and_then(|()| stdin.write_all(contents.png.as_deref().unwrap_or_default()))
+        .context("failed to send the clipboard contents to the holder")?;
+    // closing stdin tells the holder that it has everything
+    Ok(())
+}
+
+/// Run as the background holder started by [`spawn_holder`]. Stdin has `text_len` bytes of text,
+/// then optionally a PNG image to offer as well. The [`add_args`] arguments in `args` set the
+/// [`holder::Options`].
+pub fn run_holder(args: &ArgMatches, text_len: usize) -> anyhow::Result<()> {
+    let mut input = Vec::new();
+    io::stdin()
+        .read_to_end(&mut input)
+        .context("failed to read stdin")?;
+    if input.len() < text_len {
+        bail!("expected {text_len} bytes of text on stdin");
+    }
+    let png = input.split_off(text_len);
+    let text = String::from_utf8(input).context("clipboard text is not valid UTF-8")?;
+    let mut contents = Contents::new(text);
+    contents.png = (!png.is_empty()).then_some(png);
+    holder::hold(contents, holder_options(args)).context("clipboard holder failed")
+}
+
+/// The [`holder::Options`] given by the [`add_args`] arguments in `args`.
+fn holder_options(args: &ArgMatches) -> holder::Options {
+    holder::Options {
+        clear_after: args
+            .get_one::<u64>("clear-after")
+            .map(|&secs| Duration::from_secs(secs)),
+        restore: args.get_flag("restore"),
+        daemon: args.get_flag("daemonize"),
+        feedback: false,
+        max_pastes: args.get_one::<u32>("max-paste-count").copied(),
+    }
+}
+
+/// Open the file a daemonized holder logs to, `$XDG_STATE_HOME/fw/holder.log`.
+fn open_daemon_log() -> anyhow::Result<File> {
+    let dir = paths::state_dir()?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create directory '{}'", dir.display()))?;
+    let path = dir.join("holder.log");
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open log file '{}'", path.display()))
+}
+
+/// Read the clipboard back until it holds `text`, giving whoever is setting it a moment to do so.
+fn verify(text: &str) -> anyhow::Result<()> {
+    let start = Instant::now();
+    loop {
+        if selection::read_text()?.as_deref() == Some(text) {
+            return Ok(());
+        }
+        if start.elapsed() > VERIFY_TIMEOUT {
+            bail!("the clipboard doesn't hold the copied text");
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Add the clipboard-related arguments to `cmd`. These are shared by every command that copies
+/// something, and are read back by [`copy`].
+pub fn add_args(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("no-clipboard")
+            .short('n')
+            .long("no-clipboard")
+            .action(ArgAction::SetTrue)
+            .env("FW_NO_CLIPBOARD")
+            .value_parser(FalseyValueParser::new())
+            .help("Don't copy the output to the system clipboard"),
+    )
+    .arg(
+        Arg::new("no-wait")
+            .short('W')
+            .long("no-wait")
+            .action(ArgAction::SetTrue)
+            .help(
+                "Don't wait for the clipboard to be reset before exiting. (This is the \
+                       default if a clipboard manager or Gnome session is detected.) \
+                       Same as --wait-mode=no",
+            ),
+    )
+    .arg(
+        Arg::new("foreground-wait")
+            .short('F')
+            .long("foreground-wait")
+            .action(ArgAction::SetTrue)
+            .help(
+                "Wait for the clipboard to be reset in the foreground rather than \
+                       forking to the background. Same as --wait-mode=foreground",
+            ),
+    )
+    .arg(
+        Arg::new("daemonize")
+            .long("daemonize")
+            .action(ArgAction::SetTrue)
+            .help(
+                "Wait for the clipboard to be reset in a background process that's fully \
+                       detached from the terminal, logging to $XDG_STATE_HOME/fw/holder.log. \
+                       Same as --wait-mode=daemon",
+            ),
+    )
+    .arg(
+        Arg::new("wait-mode")
+            .long("wait-mode")
+            .value_name("MODE")
+            .value_parser(["auto", "no", "foreground", "background", "daemon"])
+            .env("FW_WAIT_MODE")
+            .help(
+                "How to keep the clipboard contents alive. 'auto' picks 'no' or \
+                       'background' depending on the detected desktop session and clipboard \
+                       manager",
+            ),
+    )
+    .arg(
+        Arg::new("manager")
+            .long("manager")
+            .value_name("MANAGER")
+            .value_parser(["auto", "klipper", "copyq", "none"])
+            .help(
+                "Hand the text to a clipboard manager instead of setting the clipboard \
+                       ourselves. 'auto' uses Klipper or CopyQ if one is running and no wait \
+                       mode was given",
+            ),
+    )
+    .arg(
+        Arg::new("image")
+            .long("image")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with_all(["no-wait", "manager"])
+            .help(
+                "Also offer the PNG image in FILE on the clipboard, alongside the text. \
+                       This needs fw to hold the clipboard itself, so it implies \
+                       --wait-mode=background unless another wait mode is given",
+            ),
+    )
+    .arg(
+        Arg::new("clear-after")
+            .long("clear-after")
+            .value_name("SECONDS")
+            .value_parser(value_parser!(u64))
+            .conflicts_with_all(["no-wait", "manager"])
+            .help(
+                "Put back what was on the clipboard before after SECONDS, or clear it if \
+                       that wasn't text. Nothing happens if something else was copied in the \
+                       meantime. Like --image, this implies --wait-mode=background",
+            ),
+    )
+    .arg(
+        Arg::new("restore")
+            .long("restore")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["no-wait", "manager"])
+            .help(
+                "Put back what was on the clipboard before when fw is told to stop holding \
+                       it, by --clear-after, a signal, or 'fw --kill'. Implies \
+                       --wait-mode=background like --clear-after",
+            ),
+    )
+    .arg(
+        Arg::new("max-paste-count")
+            .long("max-paste-count")
+            .value_name("N")
+            .value_parser(value_parser!(u32).range(1..))
+            // reading the clipboard back would use up one of the pastes
+            .conflicts_with_all(["no-wait", "manager", "verify"])
+            .help(
+                "Stop holding the clipboard once its contents have been pasted N times, \
+                   leaving it empty. Implies --wait-mode=background like --clear-after",
+            ),
+    )
+    .arg(
+        Arg::new("skip-duplicate")
+            .long("skip-duplicate")
+            .action(ArgAction::SetTrue)
+            .env("FW_SKIP_DUPLICATE")
+            .value_parser(FalseyValueParser::new())
+            .help(
+                "Leave the clipboard alone if it already holds the text, so that a hotkey \
+                       firing twice doesn't add it to a clipboard manager's history twice",
+            ),
+    )
+    .arg(
+        Arg::new("dry-run")
+            .long("dry-run")
+            .action(ArgAction::SetTrue)
+            .help(
+                "Print what would happen to the clipboard and history to stderr, without \
+                       touching either. The output is still printed",
+            ),
+    )
+    .arg(
+        Arg::new("verify")
+            .long("verify")
+            .value_name("ATTEMPTS")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("3")
+            .value_parser(value_parser!(u32).range(1..))
+            .help(
+                "Read the clipboard back after copying, and try again up to ATTEMPTS \
+                       times in total (default 3) if it doesn't hold the copied text",
+            ),
+    )
+}
+
+/// The mutually exclusive [`add_args`] arguments that say what to do with the clipboard. This
+/// isn't an ArgGroup because clap counts values from the environment towards those, and a
+/// setting in the environment should be overridden by the command line rather than conflict.
+const MODE_ARGS: [&str; 5] = [
+    "no-clipboard",
+    "no-wait",
+    "foreground-wait",
+    "daemonize",
+    "wait-mode",
+];
+
+/// Which of the [`MODE_ARGS`] is in effect, if any.
+fn mode_arg(args: &ArgMatches) -> anyhow::Result<Option<&'static str>> {
+    let given = |id: &str| match args.value_source(id) {
+        None | Some(ValueSource::DefaultValue) => false,
+        // flags can be set to false through the environment
+        _ => id == "wait-mode" || args.get_flag(id),
+    };
+    let from_cli: Vec<_> = MODE_ARGS
+        .into_iter()
+        .filter(|&id| given(id) && args.value_source(id) == Some(ValueSource::CommandLine))
+        .collect();
+    match from_cli[..] {
+        [] => Ok(MODE_ARGS.into_iter().find(|&id| given(id))),
+        [id] => Ok(Some(id)),
+        [a, b, ..] => bail!("the argument '--{a}' cannot be used with '--{b}'"),
+    }
+}
+
+/// Check the [`add_args`] arguments in `args` for conflicts that clap doesn't catch, so that we
+/// can fail before doing anything else.
+pub fn check_args(args: &ArgMatches) -> anyhow::Result<()> {
+    mode_arg(args).map(drop)
+}
+
+/// Whether the [`add_args`] arguments in `args` allow us to touch the clipboard at all.
+pub fn enabled(args: &ArgMatches) -> bool {
+    wanted(args) && !dry_run(args)
+}
+
+/// Whether `--dry-run` was given, so that nothing but stdout should be touched.
+pub fn dry_run(args: &ArgMatches) -> bool {
+    args.get_flag("dry-run")
+}
+
+/// Whether we'd copy anything if this weren't a `--dry-run`.
+fn wanted(args: &ArgMatches) -> bool {
+    // a conflict is reported by copy
+    !matches!(mode_arg(args), Ok(Some("no-clipboard"))) && env_is_nonempty("DISPLAY")
+}
+
+/// What [`copy`] is going to do, worked out from the arguments and the session.
+struct Plan {
+    contents: Contents,
+    manager: Option<detect::Manager>,
+    mode: WaitMode,
+    /// Where the wait mode came from, for `--dry-run`.
+    mode_reason: String,
+    options: holder::Options,
+    verify: Option<u32>,
+    skip_duplicate: bool,
+}
+
+impl Plan {
+    fn new<'s>(
+        args: &ArgMatches,
+        text: &str,
+        session: impl Fn() -> &'s detect::Session,
+    ) -> anyhow::Result<Self> {
+        let mode_id = mode_arg(args)?;
+        let explicit_mode = match mode_id {
+            Some("no-wait") => Some(WaitMode::NoWait),
+            Some("foreground-wait") => Some(WaitMode::Foreground),
+            Some("daemonize") => Some(WaitMode::Daemon),
+            Some("wait-mode") => match args.get_one::<String>("wait-mode").map(String::as_str) {
+                Some("no") => Some(WaitMode::NoWait),
+                Some("foreground") => Some(WaitMode::Foreground),
+                Some("background") => Some(WaitMode::Background),
+                Some("daemon") => Some(WaitMode::Daemon),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let mut contents = Contents::new(text.to_owned());
+        if let Some(path) = args.get_one::<PathBuf>("image") {
+            let png = fs::read(path)
+                .with_context(|| format!("failed to read image '{}'", path.display()))?;
+            if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+                bail!("'{}' is not a PNG image", path.display());
+            }
+            contents.png = Some(png);
+        }
+
+        let mut options = holder_options(args);
+        // things only our own holder can do
+        let needs_holder = contents.png.is_some()
+            || options.clear_after.is_some()
+            || options.restore
+            || options.max_pastes.is_some();
+
+        let manager = match args.get_one::<String>("manager").map(String::as_str) {
+            Some("none") => None,
+            Some("klipper") => Some(detect::Manager::Klipper),
+            Some("copyq") => Some(detect::Manager::CopyQ),
+            // an explicit wait mode means the user wants us to own the clipboard ourselves, and
+            // managers can't serve an image or restore the clipboard later
+            _ if explicit_mode.is_some() || needs_holder => None,
+            _ => session().handoff_manager(),
+        };
+
+        let (mode, mode_reason) = match (explicit_mode, mode_id) {
+            (Some(mode), Some(id)) => {
+                let reason = match args.value_source(id) {
+                    Some(ValueSource::EnvVariable) => "from $FW_WAIT_MODE".to_owned(),
+                    _ => format!("from --{id}"),
+                };
+                (mode, reason)
+            }
+            _ if needs_holder => (
+                WaitMode::Background,
+                "needed by --image, --clear-after, --restore, or --max-paste-count".to_owned(),
+            ),
+            _ if manager.is_some() => (WaitMode::NoWait, "the manager keeps the text".to_owned()),
+            _ => (
+                session().wait_mode(),
+                "detected from the session".to_owned(),
+            ),
+        };
+        if needs_holder && mode == WaitMode::NoWait {
+            bail!(
+                "--image, --clear-after, --restore, and --max-paste-count can't be used with \
+                 --wait-mode=no"
+            );
+        }
+        options.daemon = mode == WaitMode::Daemon;
+        // there's someone watching in the foreground, so tell them what's going on
+        options.feedback = mode == WaitMode::Foreground;
+
+        let verify = args.get_one::<u32>("verify").copied();
+        // in the foreground we don't get control back until the clipboard is gone again
+        if verify.is_some() && manager.is_none() && mode == WaitMode::Foreground {
+            bail!("--verify can't be used with the foreground wait mode");
+        }
+
+        Ok(Self {
+            contents,
+            manager,
+            mode,
+            mode_reason,
+            options,
+            verify,
+            skip_duplicate: args.get_flag("skip-duplicate"),
+        })
+    }
+
+    fn run(&self) -> anyhow::Result<()> {
+        let text = self.contents.text.as_str();
+        // a clipboard we can't read just gets set like it would without --skip-duplicate
+        if self.skip_duplicate && selection::read_text().ok().flatten().as_deref() == Some(text) {
+            tracing::debug!("the clipboard already holds the text, not copying it again");
+            return Ok(());
+        }
+        let set = || match self.manager {
+            Some(manager) => manager::handoff(manager, text),
+            None => set_clipboard(&self.contents, self.mode, self.options),
+        };
+        match self.verify {
+            None => set(),
+            Some(attempts) => retry(attempts, VERIFY_BACKOFF, || {
+                set()?;
+                verify(text)
+            })
+            .context("failed to verify the clipboard contents"),
+        }
+    }
+
+    /// Describe what [`run`](Self::run) would do, for `--dry-run`.
+    fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut what = format!("{} bytes of text", self.contents.text.len());
+        if let Some(png) = &self.contents.png {
+            what.push_str(&format!(" and a {} byte PNG image", png.len()));
+        }
+        match self.manager {
+            Some(manager) => lines.push(format!("would hand {what} to {manager:?}")),
+            None => lines.push(format!("would copy {what} to the CLIPBOARD selection")),
+        }
+        let how = match (self.manager, self.mode) {
+            (Some(_), _) => "nothing to wait for",
+            (None, WaitMode::NoWait) => "exit right away, leaving the text to the session",
+            (None, WaitMode::Foreground) => {
+                "hold it in the foreground until something else is copied"
+            }
+            (None, WaitMode::Background) => "hold it in a background process",
+            (None, WaitMode::Daemon) => {
+                "hold it in a detached process logging to $XDG_STATE_HOME/fw/holder.log"
+            }
+        };
+        lines.push(format!(
+            "wait mode: {} ({}), {how}",
+            self.mode.name(),
+            self.mode_reason
+        ));
+        if let Some(after) = self.options.clear_after {
+            lines.push(format!(
+                "would clear or restore the clipboard after {}s",
+                after.as_secs()
+            ));
+        }
+        if self.options.restore {
+            lines.push("would restore the previous contents when stopped".to_owned());
+        }
+        if let Some(max) = self.options.max_pastes {
+            let pastes = match max {
+                1 => "the first paste".to_owned(),
+                n => format!("{n} pastes"),
+            };
+            lines.push(format!("would stop holding the clipboard after {pastes}"));
+        }
+        if self.skip_duplicate {
+            lines.push("would skip copying if the clipboard already holds the text".to_owned());
+        }
+        if let Some(attempts) = self.verify {
+            lines.push(format!(
+                "would read the clipboard back, trying up to {attempts} times"
+            ));
+        }
+        lines
+    }
+}
+
+/// Copy `text` to the clipboard as configured by the [`add_args`] arguments in `args`. With
+/// `--dry-run`, describe what would happen on stderr instead.
+pub fn copy(args: &ArgMatches, text: &str) -> anyhow::Result<()> {
+    // only run detection if we actually need it, it spawns processes and scans /proc
+    let session = OnceCell::new();
+    let session = || session.get_or_init(detect::Session::detect);
+
+    if !dry_run(args) {
+        if wanted(args) {
+            Plan::new(args, text, session)?.run()?;
+        }
+        return Ok(());
+    }
+
+    let detected = session();
+    let mut lines = vec![format!(
+        "session: {:?}, desktop: {:?}, clipboard managers: {:?}",
+        detected.session_type, detected.desktop, detected.managers
+    )];
+    if matches!(mode_arg(args)?, Some("no-clipboard")) {
+        lines.push("wouldn't copy anything, because of --no-clipboard".to_owned());
+    } else if !env_is_nonempty("DISPLAY") {
+        lines.push("wouldn't copy anything, because $DISPLAY isn't set".to_owned());
+    } else {
+        lines.extend(Plan::new(args, text, session)?.describe());
+    }
+    // not every command that copies has --history
+    let history = matches!(args.try_get_one::<bool>("history"), Ok(Some(true)));
+    lines.push(format!(
+        "history: {}",
+        if history {
+            "would record this conversion"
+        } else {
+            "not recording"
+        }
+    ));
+    for line in lines {
+        eprintln!("fw: dry run: {line}");
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! The optional config file, `$XDG_CONFIG_HOME/fw/config.toml` unless `--config` says otherwise.
+//!
+//! ```toml
+//! # added around every output unless --prefix or --suffix say otherwise
+//! prefix = "✨ "
+//! suffix = " ✨"
+//! # never converted, unless --protect-file says otherwise
+//! protect = ["fw", "@*"]
+//!
+//! [decorations.blep]
+//! prefix = "( ˘ ³˘) "
+//! suffix = " :3"
+//!
+//! [styles.meme]
+//! chain = ["fullwidth", "spaced"]
+//! suffix = " ✨"
+//!
+//! [profiles.irc]
+//! style = "meme"
+//! max-width = 400
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::paths;
+use crate::profile::Profile;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub prefix: String,
+    pub suffix: String,
+    /// The default for `--protect-file`, one word, phrase, or pattern per item.
+    pub protect: Vec<String>,
+    pub decorations: HashMap<String, Decoration>,
+    pub styles: HashMap<String, UserStyle>,
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// Text added verbatim around the converted output, see `--decorate`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Decoration {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// A style made of other styles, see [`crate::chain`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserStyle {
+    /// The styles to convert with in turn, built-in or from the config file.
+    pub chain: Vec<String>,
+    /// Text added verbatim around the result of the chain.
+    pub prefix: String,
+    pub suffix: String,
+    /// What `fw styles` says about it.
+    pub description: String,
+}
+
+impl Config {
+    /// Load the config file from `path`, or the default location. A missing file is the same as
+    /// an empty one.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => paths::config_dir()?.join("config.toml"),
+        };
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        toml::from_str(&data).with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
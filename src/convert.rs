@@ -0,0 +1,252 @@
+//! The whole conversion pipeline in one call, for embedding fw and for fuzzing it.
+//!
+//! The input is tokenized into ANSI escapes, URLs, protected words, and template placeholders,
+//! the text between them is converted, and the result is laid out and formatted. The `fw`
+//! command converts with this too, adding the parts that need its config file, like
+//! `--decorate`, between [`convert_text`] and [`lay_out`].
+
+use crate::case::Case;
+use crate::format::Format;
+use crate::invisible::{self, Removed};
+use crate::layout::{self, Align, BoxChars, Limit, Tabs, Truncate};
+use crate::protect::Protect;
+use crate::style::{self, Chain, Chaos, Classes, Digits, Style};
+use crate::{ansi, bidi, emoji, roundtrip, template, urls};
+
+/// What [`convert`] does, like the `fw` command line options of the same names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The style, or the styles and the text around them that the `fw` command's config file
+    /// chains together.
+    pub style: Chain,
+    /// The styles for `--chaos` to pick from instead of `style`, and the seed to pick with.
+    pub chaos: Option<(Vec<Style>, u64)>,
+    /// The characters to convert, from `--only` or `--except`.
+    pub classes: Classes,
+    pub decode: bool,
+    pub decode_first: bool,
+    pub skip_converted: bool,
+    pub emoji: bool,
+    pub case: Option<Case>,
+    pub cjk_punct: bool,
+    /// The `--leet-level` for `--leet`, if given.
+    pub leet: Option<u8>,
+    pub digits: Option<Digits>,
+    pub strip_ansi: bool,
+    pub strip_invisible: bool,
+    pub keep_urls: bool,
+    /// The words from `--protect-file` to leave alone.
+    pub protect: Protect,
+    /// Whether to only convert `{{...}}` placeholders, and whether that's `--invert`ed.
+    pub template: Option<bool>,
+    /// Whether to isolate right-to-left runs, for `--bidi isolate`.
+    pub bidi_isolate: bool,
+    pub expand_tabs: Option<Tabs>,
+    pub wrap: Option<usize>,
+    pub number: bool,
+    pub quote: bool,
+    pub pad: Option<(usize, Align)>,
+    pub draw_box: Option<BoxChars>,
+    /// `--max-chars` or `--max-width`, the `--truncate-mode`, and the `--ellipsis`.
+    pub truncate: Option<(Limit, Truncate, String)>,
+    pub format: Format,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            style: Style::Fullwidth.into(),
+            chaos: None,
+            classes: Classes::ALL,
+            decode: false,
+            decode_first: false,
+            skip_converted: false,
+            emoji: false,
+            case: None,
+            cjk_punct: false,
+            leet: None,
+            digits: None,
+            strip_ansi: false,
+            strip_invisible: false,
+            keep_urls: false,
+            protect: Protect::default(),
+            template: None,
+            bidi_isolate: false,
+            expand_tabs: None,
+            wrap: None,
+            number: false,
+            quote: false,
+            pad: None,
+            draw_box: None,
+            truncate: None,
+            format: Format::Text,
+        }
+    }
+}
+
+/// The options whose random choices carry on from one part of the input to the next, so that
+/// the same seed gives the same output however the input is split up.
+struct Picks {
+    style: Chain,
+    case: Option<Case>,
+    chaos: Option<Chaos>,
+}
+
+/// Expand emoji shortcodes and such, before the style.
+fn preprocess(text: &str, options: &Options, picks: &mut Picks) -> String {
+    let mut text = text.to_owned();
+    if options.emoji {
+        text = emoji::expand(&text);
+    }
+    if let Some(case) = &mut picks.case {
+        text = case.apply_mut(&text);
+    }
+    if options.cjk_punct {
+        text = style::cjk_punct(&text);
+    }
+    if let Some(level) = options.leet {
+        text = style::leet(&text, level);
+    }
+    // after leet, so that its digits match the rest
+    if let Some(digits) = options.digits {
+        text = digits.apply(&text);
+    }
+    text
+}
+
+/// Convert or decode the visible text in `text`, leaving ANSI escapes alone.
+fn apply_style(text: &str, options: &Options, picks: &mut Picks) -> String {
+    let mut classes = options.classes;
+    // digits already took care of them
+    if options.digits.is_some() {
+        classes = classes.intersection(Classes::DIGITS.complement());
+    }
+    if let Some(chaos) = &mut picks.chaos {
+        return ansi::apply(text, |text| chaos.apply(text, classes));
+    }
+    if options.decode {
+        return picks.style.decode_with(text, |style, text| {
+            ansi::apply(text, |text| style.decode_classes(text, classes))
+        });
+    }
+    let single = picks.style.single();
+    picks.style.apply_with(text, |style, first, text| {
+        ansi::apply(text, |text| {
+            if first && options.skip_converted {
+                style.apply_unconverted_mut(text, classes)
+            } else if first && options.decode_first {
+                let decoded = style.decode_converted(text, classes);
+                style.apply_classes_mut(&decoded, classes)
+            } else {
+                debug_assert!(
+                    single.is_none() || !text.is_ascii() || roundtrip::check(*style, text).is_ok(),
+                    "{style:?} doesn't round trip {text:?}"
+                );
+                style.apply_classes_mut(text, classes)
+            }
+        })
+    })
+}
+
+/// What [`convert_text`] made of its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converted {
+    /// The input after `strip_ansi` and `strip_invisible`, and after preprocessing like `emoji`
+    /// unless that was done piece by piece for `template`, `keep_urls`, or protected words. This
+    /// is what the `fw` command records in its history.
+    pub input: String,
+    pub text: String,
+    /// What `strip_invisible` removed from the input.
+    pub removed: Removed,
+}
+
+/// The first half of [`convert`]: tokenize `text` and convert the parts between the tokens, but
+/// don't lay it out yet. The `fw` command adds the decorations from its config file in between
+/// this and [`lay_out`].
+pub fn convert_text(text: &str, options: &Options) -> Converted {
+    let mut input = text.to_owned();
+    if options.strip_ansi {
+        input = ansi::strip(&input);
+    }
+    let mut removed = Removed::default();
+    if options.strip_invisible {
+        (input, removed) = invisible::strip(&input);
+    }
+    let mut picks = Picks {
+        style: options.style.clone(),
+        case: options.case,
+        chaos: options
+            .chaos
+            .as_ref()
+            .map(|(styles, seed)| Chaos::new(styles.clone(), *seed)),
+    };
+    let mut convert_part = |part: &str| {
+        let mut convert =
+            |text: &str| apply_style(&preprocess(text, options, &mut picks), options, &mut picks);
+        options.protect.apply(part, |text| {
+            if options.keep_urls {
+                urls::apply(text, &mut convert)
+            } else {
+                convert(text)
+            }
+        })
+    };
+    let mut text = match options.template {
+        // only the parts that get converted are preprocessed, so commands stay as typed
+        Some(invert) => template::apply(&input, invert, convert_part),
+        None if options.keep_urls || !options.protect.is_empty() => convert_part(&input),
+        None => {
+            input = preprocess(&input, options, &mut picks);
+            apply_style(&input, options, &mut picks)
+        }
+    };
+
+    if options.bidi_isolate {
+        text = bidi::isolate(&text);
+    }
+    Converted {
+        input,
+        text,
+        removed,
+    }
+}
+
+/// The second half of [`convert`]: lay out and format the `text` from [`convert_text`].
+pub fn lay_out(mut text: String, options: &Options) -> String {
+    if let Some(tabs) = options.expand_tabs {
+        text = layout::expand_tabs(&text, tabs);
+    }
+    if let Some(cols) = options.wrap {
+        text = layout::wrap(&text, cols);
+    }
+    if options.number {
+        text = layout::number(&text);
+    }
+    if options.quote {
+        text = layout::quote(&text);
+    }
+    if let Some((cols, align)) = options.pad {
+        text = layout::pad(&text, cols, align);
+    }
+    if let Some(chars) = &options.draw_box {
+        text = layout::draw_box(&text, chars);
+    }
+    if let Some((limit, mode, ellipsis)) = &options.truncate {
+        text = layout::truncate(&text, *limit, *mode, ellipsis);
+    }
+    if options.format != Format::Text {
+        text = options.format.apply(&text);
+    }
+    text
+}
+
+/// Convert `text` as described by `options`.
+pub fn convert(text: &str, options: &Options) -> String {
+    lay_out(convert_text(text, options).text, options)
+}
+
+/// Like [`convert`], but for input that may not be UTF-8. Invalid bytes become U+FFFD, and the
+/// output is always UTF-8. This never panics on any input, which is what the fuzz targets check.
+pub fn convert_bytes(input: &[u8], options: &Options) -> Vec<u8> {
+    convert(&String::from_utf8_lossy(input), options).into_bytes()
+}
@@ -0,0 +1,44 @@
+//! Kaomoji and sparkle presets for `--decorate`.
+
+use anyhow::bail;
+
+use crate::config::{Config, Decoration};
+
+/// Built-in decorations as (name, prefix, suffix).
+pub const PRESETS: &[(&str, &str, &str)] = &[
+    ("sparkle", "☆♬○♩● ", " ●♩○♬☆"),
+    ("stars", "✧･ﾟ: *✧･ﾟ:* ", " *:･ﾟ✧*:･ﾟ✧"),
+    ("cate", "ฅ^•ﻌ•^ฅ ", " ฅ^•ﻌ•^ฅ"),
+    ("uwu", "(◕ᴗ◕✿) ", " uwu"),
+    ("shrug", "", " ¯\\_(ツ)_/¯"),
+    ("tableflip", "(╯°□°)╯︵ ", ""),
+    ("lenny", "", " ( ͡° ͜ʖ ͡°)"),
+];
+
+/// Find a decoration by name. The config file can override built-in presets.
+pub fn find(config: &Config, name: &str) -> anyhow::Result<Decoration> {
+    if let Some(deco) = config.decorations.get(name) {
+        return Ok(deco.clone());
+    }
+    if let Some((_, prefix, suffix)) = PRESETS.iter().find(|(n, _, _)| *n == name) {
+        return Ok(Decoration {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+        });
+    }
+
+    let mut names: Vec<&str> = PRESETS.iter().map(|(n, _, _)| *n).collect();
+    names.extend(config.decorations.keys().map(String::as_str));
+    names.sort_unstable();
+    names.dedup();
+    bail!(
+        "unknown decoration '{name}' (available: {})",
+        names.join(", ")
+    );
+}
+
+impl Decoration {
+    pub fn apply(&self, text: &str) -> String {
+        format!("{}{text}{}", self.prefix, self.suffix)
+    }
+}
@@ -0,0 +1,231 @@
+//! Desktop session and clipboard manager detection.
+//!
+//! On X11 and Wayland the clipboard contents live in the process that set them, so whether we can
+//! exit immediately after copying depends on whether something else in the session will take them
+//! over. This module pokes at the environment to make that decision for `--wait-mode auto`.
+
+use std::fs;
+use std::process::{Command, Stdio};
+
+use x11rb::protocol::xproto::ConnectionExt;
+
+use crate::clipboard::WaitMode;
+use crate::env_is_nonempty;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionType {
+    X11,
+    Wayland,
+    Tty,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Desktop {
+    Gnome,
+    Kde,
+    Other,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manager {
+    /// KDE's Klipper, found by its D-Bus name.
+    Klipper,
+    /// CopyQ, found by process name.
+    CopyQ,
+    /// wl-clip-persist, which re-offers Wayland selections after their owner exits.
+    WlClipPersist,
+    /// Some `wl-paste --watch` process (e.g. cliphist). These only record history, they don't keep
+    /// the selection alive after we exit.
+    WlPasteWatch,
+    /// Some X11 client owns the ICCCM `CLIPBOARD_MANAGER` selection, so arboard will hand off our
+    /// contents to it when the clipboard is dropped.
+    Icccm,
+}
+
+impl Manager {
+    /// Whether this manager will keep serving our clipboard contents after we exit.
+    pub fn persists_clipboard(self) -> bool {
+        !matches!(self, Manager::WlPasteWatch)
+    }
+
+    /// Whether we can give text to this manager directly, see [`crate::manager::handoff`].
+    pub fn supports_handoff(self) -> bool {
+        matches!(self, Manager::Klipper | Manager::CopyQ)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub session_type: SessionType,
+    pub desktop: Desktop,
+    pub managers: Vec<Manager>,
+}
+
+impl Session {
+    pub fn detect() -> Self {
+        let session_type = match std::env::var("XDG_SESSION_TYPE").as_deref() {
+            Ok("x11") => SessionType::X11,
+            Ok("wayland") => SessionType::Wayland,
+            Ok("tty") => SessionType::Tty,
+            _ if env_is_nonempty("WAYLAND_DISPLAY") => SessionType::Wayland,
+            _ if env_is_nonempty("DISPLAY") => SessionType::X11,
+            _ => SessionType::Unknown,
+        };
+
+        // XDG_CURRENT_DESKTOP is a colon-separated list, e.g. "ubuntu:GNOME"
+        let desktop = match std::env::var("XDG_CURRENT_DESKTOP") {
+            Ok(val) if !val.is_empty() => {
+                let names: Vec<String> = val.split(':').map(str::to_ascii_lowercase).collect();
+                if names.iter().any(|n| n == "gnome") {
+                    Desktop::Gnome
+                } else if names.iter().any(|n| n == "kde") {
+                    Desktop::Kde
+                } else {
+                    Desktop::Other
+                }
+            }
+            _ => Desktop::None,
+        };
+
+        let mut managers = Vec::new();
+        if dbus_name_has_owner("org.kde.klipper") {
+            managers.push(Manager::Klipper);
+        }
+        for cmdline in process_cmdlines() {
+            let mut args = cmdline.iter().map(|arg| arg_basename(arg));
+            let manager = match args.next() {
+                Some("copyq") => Manager::CopyQ,
+                Some("wl-clip-persist") => Manager::WlClipPersist,
+                Some("wl-paste") if args.any(|arg| arg == "-w" || arg == "--watch") => {
+                    Manager::WlPasteWatch
+                }
+                _ => continue,
+            };
+            if !managers.contains(&manager) {
+                managers.push(manager);
+            }
+        }
+        if session_type != SessionType::Tty && x11_has_clipboard_manager() {
+            managers.push(Manager::Icccm);
+        }
+
+        Self {
+            session_type,
+            desktop,
+            managers,
+        }
+    }
+
+    /// The first detected manager that we can hand text to directly, if any.
+    pub fn handoff_manager(&self) -> Option<Manager> {
+        self.managers.iter().copied().find(|m| m.supports_handoff())
+    }
+
+    /// Pick the wait mode to use when the user didn't request one explicitly.
+    pub fn wait_mode(&self) -> WaitMode {
+        if self.managers.iter().any(|m| m.persists_clipboard()) {
+            // something else will take over the contents once we're gone
+            WaitMode::NoWait
+        } else if self.desktop == Desktop::Gnome
+            && matches!(self.session_type, SessionType::X11 | SessionType::Wayland)
+        {
+            // In Gnome, it seems like we can get away with setting the clipboard then immediately
+            // exiting. I guess mutter picks it up. Don't trust a leftover XDG_CURRENT_DESKTOP
+            // from e.g. an ssh session with X forwarding though.
+            WaitMode::NoWait
+        } else {
+            // KDE without Klipper, bare window managers, and anything else we don't recognize:
+            // wait in the background to serve clipboard requests.
+            WaitMode::Background
+        }
+    }
+}
+
+/// Get the final path component of a process argument, e.g. `/usr/bin/copyq` -> `copyq`.
+fn arg_basename(arg: &str) -> &str {
+    arg.rsplit('/').next().unwrap_or(arg)
+}
+
+/// Read the command lines of all processes visible in /proc. Processes we can't read (because
+/// they exited or belong to another user) are silently skipped.
+fn process_cmdlines() -> Vec<Vec<String>> {
+    let Ok(dir) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    dir.filter_map(|ent| {
+        let ent = ent.ok()?;
+        if !ent
+            .file_name()
+            .to_str()?
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let raw = fs::read(ent.path().join("cmdline")).ok()?;
+        let args: Vec<String> = raw
+            .split(|&b| b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        (!args.is_empty()).then_some(args)
+    })
+    .collect()
+}
+
+/// Ask the session bus whether a well-known name is owned. We shell out to `dbus-send` rather
+/// than link a D-Bus library; if it's not installed or there's no session bus, this is false.
+fn dbus_name_has_owner(name: &str) -> bool {
+    if !env_is_nonempty("DBUS_SESSION_BUS_ADDRESS") {
+        return false;
+    }
+    let output = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply=literal",
+            "--dest=org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus.NameHasOwner",
+        ])
+        .arg(format!("string:{name}"))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(out) if out.status.success() => {
+            // literal output is something like "   boolean true"
+            String::from_utf8_lossy(&out.stdout)
+                .split_whitespace()
+                .last()
+                == Some("true")
+        }
+        _ => false,
+    }
+}
+
+/// Check whether any X11 client owns the `CLIPBOARD_MANAGER` selection.
+///
+/// This opens its own short-lived connection rather than going through arboard, whose helper
+/// thread would be overkill for one request.
+fn x11_has_clipboard_manager() -> bool {
+    if !env_is_nonempty("DISPLAY") {
+        return false;
+    }
+    let check = || -> Option<bool> {
+        let (conn, _screen) = x11rb::connect(None).ok()?;
+        let atom = conn
+            .intern_atom(true, b"CLIPBOARD_MANAGER")
+            .ok()?
+            .reply()
+            .ok()?
+            .atom;
+        if atom == x11rb::NONE {
+            return Some(false);
+        }
+        let owner = conn.get_selection_owner(atom).ok()?.reply().ok()?.owner;
+        Some(owner != x11rb::NONE)
+    };
+    check().unwrap_or(false)
+}
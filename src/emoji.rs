@@ -0,0 +1,204 @@
+//! `:shortcode:` to emoji expansion.
+
+/// Shortcodes and the emoji they expand to, sorted by shortcode for binary search. Names follow
+/// the GitHub/Slack conventions.
+#[rustfmt::skip]
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("alien", "👽"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("arrow_down", "⬇️"),
+    ("arrow_left", "⬅️"),
+    ("arrow_right", "➡️"),
+    ("arrow_up", "⬆️"),
+    ("baby", "👶"),
+    ("balloon", "🎈"),
+    ("banana", "🍌"),
+    ("bear", "🐻"),
+    ("beer", "🍺"),
+    ("bell", "🔔"),
+    ("bird", "🐦"),
+    ("blue_heart", "💙"),
+    ("blush", "😊"),
+    ("bomb", "💣"),
+    ("boom", "💥"),
+    ("bread", "🍞"),
+    ("broken_heart", "💔"),
+    ("bug", "🐛"),
+    ("cake", "🍰"),
+    ("camera", "📷"),
+    ("cat", "🐱"),
+    ("cat2", "🐈"),
+    ("check", "✔️"),
+    ("cherry_blossom", "🌸"),
+    ("chicken", "🐔"),
+    ("clap", "👏"),
+    ("clown_face", "🤡"),
+    ("coffee", "☕"),
+    ("cold_sweat", "😰"),
+    ("cool", "🆒"),
+    ("cow", "🐮"),
+    ("crab", "🦀"),
+    ("crown", "👑"),
+    ("cry", "😢"),
+    ("crying_cat_face", "😿"),
+    ("dog", "🐶"),
+    ("dog2", "🐕"),
+    ("dragon", "🐉"),
+    ("duck", "🦆"),
+    ("eggplant", "🍆"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("fish", "🐟"),
+    ("flushed", "😳"),
+    ("fox_face", "🦊"),
+    ("frog", "🐸"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hamburger", "🍔"),
+    ("hash", "#️⃣"),
+    ("hear_no_evil", "🙉"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("heart_eyes_cat", "😻"),
+    ("hearts", "♥️"),
+    ("heavy_check_mark", "✔️"),
+    ("hugs", "🤗"),
+    ("hundred", "💯"),
+    ("innocent", "😇"),
+    ("joy", "😂"),
+    ("joy_cat", "😹"),
+    ("kiss", "💋"),
+    ("kissing_cat", "😽"),
+    ("koala", "🐨"),
+    ("laughing", "😆"),
+    ("lemon", "🍋"),
+    ("lion", "🦁"),
+    ("lock", "🔒"),
+    ("mega", "📣"),
+    ("monkey", "🐒"),
+    ("monkey_face", "🐵"),
+    ("moon", "🌙"),
+    ("mouse", "🐭"),
+    ("muscle", "💪"),
+    ("musical_note", "🎵"),
+    ("nerd_face", "🤓"),
+    ("no_entry", "⛔"),
+    ("notes", "🎶"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("owl", "🦉"),
+    ("panda_face", "🐼"),
+    ("party", "🥳"),
+    ("partying_face", "🥳"),
+    ("peach", "🍑"),
+    ("penguin", "🐧"),
+    ("pensive", "😔"),
+    ("pig", "🐷"),
+    ("pizza", "🍕"),
+    ("poop", "💩"),
+    ("pouting_cat", "😾"),
+    ("pray", "🙏"),
+    ("purple_heart", "💜"),
+    ("rabbit", "🐰"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("relaxed", "☺️"),
+    ("relieved", "😌"),
+    ("robot", "🤖"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("rose", "🌹"),
+    ("sake", "🍶"),
+    ("scream", "😱"),
+    ("scream_cat", "🙀"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("smile", "😄"),
+    ("smile_cat", "😸"),
+    ("smiley", "😃"),
+    ("smiley_cat", "😺"),
+    ("smirk", "😏"),
+    ("smirk_cat", "😼"),
+    ("snail", "🐌"),
+    ("snake", "🐍"),
+    ("snowflake", "❄️"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("sparkling_heart", "💖"),
+    ("speak_no_evil", "🙊"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("sushi", "🍣"),
+    ("sweat_smile", "😅"),
+    ("taco", "🌮"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("tiger", "🐯"),
+    ("trophy", "🏆"),
+    ("turtle", "🐢"),
+    ("unamused", "😒"),
+    ("unicorn", "🦄"),
+    ("upside_down_face", "🙃"),
+    ("v", "✌️"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("weary", "😩"),
+    ("whale", "🐳"),
+    ("wink", "😉"),
+    ("wolf", "🐺"),
+    ("x", "❌"),
+    ("yellow_heart", "💛"),
+    ("yum", "😋"),
+    ("zap", "⚡"),
+    ("zzz", "💤"),
+];
+
+pub fn lookup(shortcode: &str) -> Option<&'static str> {
+    SHORTCODES
+        .binary_search_by(|(name, _)| (*name).cmp(shortcode))
+        .ok()
+        .map(|i| SHORTCODES[i].1)
+}
+
+/// Replace every `:shortcode:` in `text` with its emoji. Unknown shortcodes are left alone, so
+/// ordinary colons (like in `12:30` or `note: ...`) survive.
+pub fn expand(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let code_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')))
+            .unwrap_or(after.len());
+        match (
+            after[code_len..].starts_with(':'),
+            lookup(&after[..code_len]),
+        ) {
+            (true, Some(emoji)) if code_len > 0 => {
+                out.push_str(emoji);
+                rest = &after[code_len + 1..];
+            }
+            _ => {
+                // not a shortcode, keep the colon and keep scanning right after it, since it
+                // might be the closing colon of a failed match and the opening of a real one
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,83 @@
+//! How converted text is serialized for output, see `--format`.
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The text as-is.
+    Text,
+    /// Non-ASCII characters as Rust-style `\u{FF48}` escapes.
+    UnicodeEscape,
+    /// Non-ASCII characters as `&#xFF48;` entities, for systems that strip non-ASCII. HTML
+    /// special characters are escaped too.
+    HtmlEntities,
+    /// A quoted JSON string.
+    JsonString,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [
+        Format::Text,
+        Format::UnicodeEscape,
+        Format::HtmlEntities,
+        Format::JsonString,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::UnicodeEscape => "unicode-escape",
+            Format::HtmlEntities => "html-entities",
+            Format::JsonString => "json-string",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Serialize `text` in this format.
+    pub fn apply(self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() * 2);
+        match self {
+            Format::Text => out.push_str(text),
+            Format::UnicodeEscape => {
+                for c in text.chars() {
+                    if c.is_ascii() {
+                        out.push(c);
+                    } else {
+                        write!(out, "\\u{{{:X}}}", c as u32).unwrap();
+                    }
+                }
+            }
+            Format::HtmlEntities => {
+                for c in text.chars() {
+                    match c {
+                        '&' => out.push_str("&amp;"),
+                        '<' => out.push_str("&lt;"),
+                        '>' => out.push_str("&gt;"),
+                        '"' => out.push_str("&quot;"),
+                        c if c.is_ascii() => out.push(c),
+                        c => write!(out, "&#x{:X};", c as u32).unwrap(),
+                    }
+                }
+            }
+            Format::JsonString => {
+                out.push('"');
+                for c in text.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        '\t' => out.push_str("\\t"),
+                        c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,251 @@
+//! Conversion history, stored in `$XDG_DATA_HOME/fw/history`.
+//!
+//! The file is append-only with one entry per line: a unix timestamp, the original text, and the
+//! converted text, separated by tabs. Backslashes, tabs, and newlines in the text are escaped so
+//! that multi-line conversions still fit on one line.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Command as Process, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::{clipboard, paths, picker};
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub original: String,
+    pub converted: String,
+}
+
+fn path() -> anyhow::Result<PathBuf> {
+    Ok(paths::data_dir()?.join("history"))
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Append a conversion to the history file, creating it if needed.
+pub fn append(original: &str, converted: &str) -> anyhow::Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let line = format!("{time}\t{}\t{}\n", escape(original), escape(converted));
+
+    // a single write of the whole line so that concurrent fw processes don't interleave entries
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| f.write_all(line.as_bytes()))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Load all history entries, most recent first. A missing history file is just empty history.
+pub fn load() -> anyhow::Result<Vec<Entry>> {
+    let path = path()?;
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", path.display())),
+    };
+
+    let mut entries: Vec<Entry> = data
+        .lines()
+        .filter_map(|line| {
+            // skip lines we can't parse rather than making the whole history unusable
+            let mut fields = line.splitn(3, '\t');
+            let _time: u64 = fields.next()?.parse().ok()?;
+            let original = unescape(fields.next()?);
+            let converted = unescape(fields.next()?);
+            Some(Entry {
+                original,
+                converted,
+            })
+        })
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+pub fn command() -> Command {
+    clipboard::add_args(Command::new("history"))
+        .about("List, search, and re-copy previous conversions")
+        .long_about(
+            "List, search, and re-copy previous conversions. Entries are numbered starting \
+             from 1 for the most recent. Only conversions made with --history are recorded.",
+        )
+        .arg(
+            Arg::new("copy")
+                .short('c')
+                .long("copy")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with("pick")
+                .help("Copy entry N to the clipboard again"),
+        )
+        .arg(
+            Arg::new("pick")
+                .short('p')
+                .long("pick")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Choose an entry to copy with a fuzzy finder, previewing both texts \
+                     (or with $FW_PICKER if it's set, such as fzf)",
+                ),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Only list the N most recent matching entries"),
+        )
+        .arg(
+            Arg::new("search")
+                .value_name("PATTERN")
+                .help("Only show entries whose original or converted text contains PATTERN"),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let mut entries: Vec<(usize, Entry)> = load()?
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| (i + 1, e))
+        .collect();
+
+    // entry numbers count the whole history, so look it up before any of it is filtered out
+    if let Some(&n) = args.get_one::<usize>("copy") {
+        let Some((_, entry)) = entries.iter().find(|(i, _)| *i == n) else {
+            bail!("no history entry {n}");
+        };
+        println!("{}", entry.converted);
+        return clipboard::copy(args, &entry.converted);
+    }
+
+    if let Some(pattern) = args.get_one::<String>("search") {
+        let pattern = pattern.to_lowercase();
+        entries.retain(|(_, e)| {
+            e.original.to_lowercase().contains(&pattern)
+                || e.converted.to_lowercase().contains(&pattern)
+        });
+    }
+    if let Some(&limit) = args.get_one::<usize>("limit") {
+        entries.truncate(limit);
+    }
+
+    if args.get_flag("pick") {
+        let Some(entry) = pick(&entries)? else {
+            return Ok(());
+        };
+        println!("{}", entry.converted);
+        return clipboard::copy(args, &entry.converted);
+    }
+
+    for (i, entry) in &entries {
+        println!(
+            "{i:>4}\t{}\t{}",
+            escape(&entry.converted),
+            escape(&entry.original)
+        );
+    }
+    Ok(())
+}
+
+/// Let the user choose one of `entries`, returning None if they cancelled.
+fn pick(entries: &[(usize, Entry)]) -> anyhow::Result<Option<&Entry>> {
+    if let Ok(picker) = std::env::var("FW_PICKER") {
+        return pick_external(&picker, entries);
+    }
+    let items: Vec<picker::Item> = entries
+        .iter()
+        .map(|(i, entry)| picker::Item {
+            label: format!("{i:>4}  {}  {}", entry.converted, entry.original),
+            preview: format!(
+                "original:\n{}\n\nconverted:\n{}",
+                entry.original, entry.converted
+            ),
+        })
+        .collect();
+    Ok(picker::pick(&items)?.map(|i| &entries[i].1))
+}
+
+/// Run an external picker such as fzf over `entries` and return the chosen one, or None if the
+/// picker was cancelled.
+fn pick_external<'a>(
+    picker: &str,
+    entries: &'a [(usize, Entry)],
+) -> anyhow::Result<Option<&'a Entry>> {
+    let mut child = Process::new("sh")
+        .args(["-c", picker])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to run picker '{picker}'"))?;
+
+    let mut input = String::new();
+    for (i, entry) in entries {
+        input.push_str(&format!(
+            "{i}\t{}\t{}\n",
+            escape(&entry.converted),
+            escape(&entry.original)
+        ));
+    }
+    // the picker may exit before reading everything, which isn't our problem
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+
+    let output = child
+        .wait_with_output()
+        .context("failed to wait for picker")?;
+    if !output.status.success() {
+        // fzf exits 130 on ctrl-c/esc and 1 for no match, neither is an error for us
+        return Ok(None);
+    }
+    let selected = String::from_utf8_lossy(&output.stdout);
+    let Some(n) = selected
+        .split('\t')
+        .next()
+        .and_then(|n| n.trim().parse::<usize>().ok())
+    else {
+        return Ok(None);
+    };
+    Ok(entries.iter().find(|(i, _)| *i == n).map(|(_, e)| e))
+}
@@ -0,0 +1,603 @@
+//! Owning the X11 clipboard selection ourselves while waiting for it to be reset.
+//!
+//! arboard's wait API only ever offers plain text. Serving the selection directly lets us offer
+//! the same contents as several targets at once, so that applications can pick the one they like
+//! best: plain text for terminals, HTML for rich text editors, and an optional PNG image.
+
+use std::io;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt, CreateWindowAux, EventMask, PropMode,
+    Property, SelectionNotifyEvent, SelectionRequestEvent, Timestamp, Window, WindowClass,
+    SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, NONE};
+
+use crate::{selection, signals};
+
+/// The name of the holder's window, so that `fw targets` can tell when fw owns the clipboard.
+pub const WINDOW_NAME: &str = "fw clipboard holder";
+
+/// The property on the holder's window with the unix time it started at.
+pub const STARTED_PROPERTY: &str = "FW_STARTED";
+
+/// How the holder behaves besides serving the clipboard.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Stop serving our contents after this long.
+    pub clear_after: Option<Duration>,
+    /// Put back the previous contents when we're told to exit as well.
+    pub restore: bool,
+    /// Log what we're doing to stderr and exit cleanly on signals, for running as a daemon.
+    pub daemon: bool,
+    /// Tell the user on stderr when something is pasted and when something else takes the
+    /// clipboard, for waiting in the foreground.
+    pub feedback: bool,
+    /// Stop after serving this many pastes.
+    pub max_pastes: Option<u32>,
+}
+
+/// Everything we offer on the clipboard.
+#[derive(Debug, Clone)]
+pub struct Contents {
+    pub text: String,
+    pub html: String,
+    pub png: Option<Vec<u8>>,
+}
+
+impl Contents {
+    /// Offer `text` as plain text and as HTML.
+    pub fn new(text: String) -> Self {
+        let html = html(&text);
+        Self {
+            text,
+            html,
+            png: None,
+        }
+    }
+}
+
+/// Wrap `text` in just enough HTML that its spacing survives being pasted as rich text.
+fn html(text: &str) -> String {
+    let mut out = String::from(r#"<meta charset="utf-8"><span style="white-space: pre-wrap">"#);
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out.push_str("</span>");
+    out
+}
+
+struct Atoms {
+    clipboard: Atom,
+    targets: Atom,
+    timestamp: Atom,
+    incr: Atom,
+    utf8_string: Atom,
+    text_plain_utf8: Atom,
+    text_plain: Atom,
+    text: Atom,
+    html: Atom,
+    png: Atom,
+}
+
+/// A reply too big for a single property, being sent in pieces with the INCR protocol.
+struct Transfer {
+    requestor: Window,
+    property: Atom,
+    kind: Atom,
+    data: Vec<u8>,
+    /// How much of `data` has been sent so far.
+    sent: usize,
+}
+
+struct Holder {
+    conn: RustConnection,
+    atoms: Atoms,
+    window: Window,
+    /// When we took ownership, which is what we answer for the TIMESTAMP target.
+    time: Timestamp,
+    contents: Contents,
+    /// When `--clear-after` runs out.
+    deadline: Option<Instant>,
+    /// What was on the clipboard before we took it, to go back to when we're done. The inner
+    /// option is None if that wasn't text.
+    prior: Option<Option<Contents>>,
+    /// The read end of the pipe our signal handlers write to, if we've installed them.
+    signals: Option<OwnedFd>,
+    /// Whether to go back to `prior` on the first signal, rather than exiting.
+    restore: bool,
+    /// Whether to [`log`](Self::log) anything.
+    verbose: bool,
+    /// Whether to tell the user about pastes, see [`Options::feedback`].
+    feedback: bool,
+    /// How many times our contents have been pasted.
+    pastes: u32,
+    max_pastes: Option<u32>,
+    /// The most we put in one property, larger replies use INCR.
+    chunk_size: usize,
+    transfers: Vec<Transfer>,
+}
+
+impl Holder {
+    fn connect(contents: Contents, options: Options) -> anyhow::Result<Self> {
+        // this has to happen before we take the clipboard over. It's not worth failing the copy
+        // if the old owner doesn't answer, we'll just clear the clipboard instead.
+        let prior = (options.clear_after.is_some() || options.restore)
+            .then(|| selection::read_text().ok().flatten().map(Contents::new));
+        let deadline = options.clear_after.map(|timeout| Instant::now() + timeout);
+        let signals = if options.restore || options.daemon || options.feedback {
+            Some(signals::catch(&[
+                libc::SIGTERM,
+                libc::SIGINT,
+                libc::SIGHUP,
+            ])?)
+        } else {
+            None
+        };
+
+        let (conn, screen_num) =
+            x11rb::connect(None).context("failed to connect to the X server")?;
+        let intern = |name: &[u8]| -> anyhow::Result<Atom> {
+            Ok(conn
+                .intern_atom(false, name)
+                .context("failed to intern atom")?
+                .reply()
+                .context("failed to intern atom")?
+                .atom)
+        };
+        let atoms = Atoms {
+            clipboard: intern(b"CLIPBOARD")?,
+            targets: intern(b"TARGETS")?,
+            timestamp: intern(b"TIMESTAMP")?,
+            incr: intern(b"INCR")?,
+            utf8_string: intern(b"UTF8_STRING")?,
+            text_plain_utf8: intern(b"text/plain;charset=utf-8")?,
+            text_plain: intern(b"text/plain")?,
+            text: intern(b"TEXT")?,
+            html: intern(b"text/html")?,
+            png: intern(b"image/png")?,
+        };
+
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id().context("failed to create a window")?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )
+        .context("failed to create a window")?;
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            WINDOW_NAME.as_bytes(),
+        )
+        .context("failed to name the window")?;
+        // so that `fw --kill` knows who to signal, and `fw status` has something to say
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs().try_into().unwrap_or(u32::MAX));
+        for (name, value) in [
+            (&b"_NET_WM_PID"[..], std::process::id()),
+            (STARTED_PROPERTY.as_bytes(), started),
+        ] {
+            conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                intern(name)?,
+                AtomEnum::CARDINAL,
+                &[value],
+            )
+            .context("failed to name the window")?;
+        }
+        conn.flush().context("failed to create a window")?;
+
+        // the ICCCM says not to take a selection with CurrentTime, and the only way to get a
+        // server timestamp is from an event, so the WM_NAME change above doubles as that
+        let time = loop {
+            match conn.wait_for_event().context("X connection failed")? {
+                Event::PropertyNotify(event) if event.window == window => break event.time,
+                _ => (),
+            }
+        };
+
+        let chunk_size = (conn.maximum_request_bytes() / 4).max(4096);
+        Ok(Self {
+            conn,
+            atoms,
+            window,
+            time,
+            contents,
+            deadline,
+            prior,
+            signals,
+            restore: options.restore,
+            verbose: options.daemon,
+            feedback: options.feedback,
+            pastes: 0,
+            max_pastes: options.max_pastes,
+            chunk_size,
+            transfers: Vec::new(),
+        })
+    }
+
+    fn targets(&self) -> Vec<Atom> {
+        let a = &self.atoms;
+        let mut targets = vec![
+            a.targets,
+            a.timestamp,
+            a.utf8_string,
+            a.text_plain_utf8,
+            a.text_plain,
+            a.text,
+            AtomEnum::STRING.into(),
+            a.html,
+        ];
+        if self.contents.png.is_some() {
+            targets.push(a.png);
+        }
+        targets
+    }
+
+    /// The data and property type to answer a request for `target` with.
+    fn data_for(&self, target: Atom) -> Option<(&[u8], Atom)> {
+        let a = &self.atoms;
+        let contents = &self.contents;
+        if target == a.utf8_string || target == a.text || target == a.text_plain_utf8 {
+            // TEXT lets the owner pick the type, and every client asking for it handles UTF-8
+            let kind = if target == a.text {
+                a.utf8_string
+            } else {
+                target
+            };
+            Some((contents.text.as_bytes(), kind))
+        } else if target == a.text_plain || target == Atom::from(AtomEnum::STRING) {
+            // these are supposed to be Latin-1 or ASCII, but sending UTF-8 is what everyone does
+            Some((contents.text.as_bytes(), target))
+        } else if target == a.html {
+            Some((contents.html.as_bytes(), target))
+        } else if target == a.png {
+            contents.png.as_deref().map(|png| (png, target))
+        } else {
+            None
+        }
+    }
+
+    /// Answer a SelectionRequest, returning the property we stored the reply in, if any.
+    fn reply(&mut self, event: &SelectionRequestEvent) -> anyhow::Result<Atom> {
+        if event.selection != self.atoms.clipboard {
+            return Ok(NONE);
+        }
+        // obsolete clients leave the property unset and expect the target to be used instead
+        let property = if event.property == NONE {
+            event.target
+        } else {
+            event.property
+        };
+
+        if event.target == self.atoms.targets {
+            self.conn.change_property32(
+                PropMode::REPLACE,
+                event.requestor,
+                property,
+                AtomEnum::ATOM,
+                &self.targets(),
+            )?;
+        } else if event.target == self.atoms.timestamp {
+            self.conn.change_property32(
+                PropMode::REPLACE,
+                event.requestor,
+                property,
+                AtomEnum::INTEGER,
+                &[self.time],
+            )?;
+        } else if let Some((data, kind)) = self.data_for(event.target) {
+            if data.len() > self.chunk_size {
+                let transfer = Transfer {
+                    requestor: event.requestor,
+                    property,
+                    kind,
+                    data: data.to_vec(),
+                    sent: 0,
+                };
+                // watch for the requestor deleting the property, which asks for the next piece
+                self.conn.change_window_attributes(
+                    event.requestor,
+                    &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+                )?;
+                self.conn.change_property32(
+                    PropMode::REPLACE,
+                    event.requestor,
+                    property,
+                    self.atoms.incr,
+                    &[data.len().try_into().unwrap_or(u32::MAX)],
+                )?;
+                self.transfers
+                    .retain(|t| (t.requestor, t.property) != (event.requestor, property));
+                self.transfers.push(transfer);
+            } else {
+                self.conn.change_property8(
+                    PropMode::REPLACE,
+                    event.requestor,
+                    property,
+                    kind,
+                    data,
+                )?;
+            }
+            // TARGETS and TIMESTAMP are just questions, this is somebody actually pasting
+            self.pastes += 1;
+        } else {
+            return Ok(NONE);
+        }
+        Ok(property)
+    }
+
+    /// Send the next piece of an INCR transfer once the requestor has read the last one.
+    fn continue_transfer(&mut self, window: Window, property: Atom) -> anyhow::Result<()> {
+        let Some(i) = self
+            .transfers
+            .iter()
+            .position(|t| (t.requestor, t.property) == (window, property))
+        else {
+            return Ok(());
+        };
+        let transfer = &mut self.transfers[i];
+        let end = transfer.data.len().min(transfer.sent + self.chunk_size);
+        let chunk = &transfer.data[transfer.sent..end];
+        // the final piece is empty, after which the transfer is done
+        self.conn
+            .change_property8(PropMode::REPLACE, window, property, transfer.kind, chunk)?;
+        let done = chunk.is_empty();
+        transfer.sent = end;
+        if done {
+            self.transfers.swap_remove(i);
+            self.conn.change_window_attributes(
+                window,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the next event, a signal, or `deadline`.
+    fn next_event(&self, deadline: Option<Instant>) -> anyhow::Result<Wake> {
+        loop {
+            if let Some(event) = self.conn.poll_for_event().context("X connection failed")? {
+                return Ok(Wake::Event(event));
+            }
+            let timeout = match deadline {
+                None => -1,
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    None => return Ok(Wake::Timeout),
+                    // round up so that we don't wake up just before the deadline
+                    Some(left) => (left.as_millis() + 1).try_into().unwrap_or(i32::MAX),
+                },
+            };
+            let mut fds = [
+                libc::pollfd {
+                    fd: self.conn.stream().as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: self.signals.as_ref().map_or(-1, |fd| fd.as_raw_fd()),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            // SAFETY: fds is a valid array of pollfds, poll ignores the negative fd
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) } < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err).context("failed to wait for X events");
+                }
+            }
+            if let Some(pipe) = self.signals.as_ref().filter(|_| fds[1].revents != 0) {
+                signals::drain(pipe);
+                return Ok(Wake::Signal);
+            }
+        }
+    }
+
+    fn log(&self, message: &str) {
+        if self.verbose {
+            tracing::info!("{message}");
+        }
+    }
+
+    /// Log `message`, or tell the user if they're waiting for us in the foreground.
+    fn tell(&self, message: &str) {
+        if self.verbose || self.feedback {
+            tracing::info!("{message}");
+        }
+    }
+
+    /// How many pastes we've served, as part of a message.
+    fn paste_count(&self) -> String {
+        match self.pastes {
+            1 => "pasted once".to_owned(),
+            n => format!("pasted {n} times"),
+        }
+    }
+
+    /// Whether we've served `--max-paste-count` pastes, and finished sending all of them.
+    fn pastes_done(&self) -> bool {
+        self.max_pastes.is_some_and(|max| self.pastes >= max) && self.transfers.is_empty()
+    }
+
+    /// Stop serving our contents, going back to `prior` or clearing the clipboard.
+    fn expire(&mut self, prior: Option<Contents>) -> anyhow::Result<()> {
+        self.transfers.clear();
+        match prior {
+            // carry on as the owner, just with the old contents. If somebody else took the
+            // clipboard in the meantime, their SelectionClear is on its way and we'll exit
+            // without having served anything.
+            Some(prior) => {
+                self.log("putting back the previous clipboard contents");
+                self.contents = prior;
+            }
+            // our ownership timestamp makes this a no-op if the clipboard changed hands since,
+            // otherwise the server tells us we lost it like it would for anyone else
+            None => {
+                self.log("clearing the clipboard");
+                self.conn
+                    .set_selection_owner(NONE, self.atoms.clipboard, self.time)
+                    .context("failed to clear the clipboard")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the clipboard and serve it until some other client takes it from us.
+    fn run(&mut self) -> anyhow::Result<()> {
+        self.conn
+            .set_selection_owner(self.window, self.atoms.clipboard, self.time)
+            .context("failed to set the clipboard owner")?;
+        let owner = self
+            .conn
+            .get_selection_owner(self.atoms.clipboard)
+            .context("failed to set the clipboard owner")?
+            .reply()
+            .context("failed to set the clipboard owner")?
+            .owner;
+        if owner != self.window {
+            bail!("failed to set the clipboard owner");
+        }
+        self.log("holding the clipboard");
+        if self.feedback {
+            tracing::info!("holding the clipboard until something else is copied");
+        }
+
+        loop {
+            let event = match self.next_event(self.deadline)? {
+                Wake::Event(event) => event,
+                Wake::Timeout => {
+                    self.deadline = None;
+                    if let Some(prior) = self.prior.take() {
+                        self.expire(prior)?;
+                    }
+                    self.conn.flush().context("X connection failed")?;
+                    continue;
+                }
+                // the first signal puts the old contents back, and we keep serving them like
+                // after a timeout. There's nothing left to do for any more signals after that.
+                Wake::Signal => match self.prior.take().filter(|_| self.restore) {
+                    Some(prior) => {
+                        self.deadline = None;
+                        self.expire(prior)?;
+                        self.conn.flush().context("X connection failed")?;
+                        continue;
+                    }
+                    None => {
+                        self.tell(&format!("{}, exiting on signal", self.paste_count()));
+                        return Ok(());
+                    }
+                },
+            };
+            match event {
+                Event::SelectionRequest(event) => {
+                    let pastes = self.pastes;
+                    let property = self.reply(&event)?;
+                    if self.feedback && self.pastes > pastes {
+                        tracing::info!("{}", self.paste_count());
+                    }
+                    let notify = SelectionNotifyEvent {
+                        response_type: SELECTION_NOTIFY_EVENT,
+                        sequence: 0,
+                        time: event.time,
+                        requestor: event.requestor,
+                        selection: event.selection,
+                        target: event.target,
+                        property,
+                    };
+                    self.conn
+                        .send_event(false, event.requestor, EventMask::NO_EVENT, notify)?;
+                }
+                Event::PropertyNotify(event) if event.state == Property::DELETE => {
+                    self.continue_transfer(event.window, event.atom)?;
+                }
+                Event::SelectionClear(event) if event.selection == self.atoms.clipboard => {
+                    self.tell(&format!(
+                        "something else was copied after fw's text was {}, exiting",
+                        self.paste_count()
+                    ));
+                    return Ok(());
+                }
+                // errors for requests to requestors which went away in the meantime, nothing
+                // else we can do about those
+                _ => (),
+            }
+            self.conn.flush().context("X connection failed")?;
+            // the clipboard empties once we're gone, like it would after a timeout without
+            // anything to put back
+            if self.pastes_done() {
+                self.tell(&format!("{}, exiting", self.paste_count()));
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// What woke the holder up.
+enum Wake {
+    Event(Event),
+    Timeout,
+    Signal,
+}
+
+/// Own the clipboard with `contents`, returning once another client takes it over. After
+/// `options.clear_after`, the clipboard goes back to what it held before, or is cleared if it
+/// wasn't text. With `options.restore`, the same happens when we get a signal.
+pub fn hold(contents: Contents, options: Options) -> anyhow::Result<()> {
+    Holder::connect(contents, options)?.run()
+}
+
+/// The window of the fw holding `selection`, if that's who owns it.
+pub fn find(reader: &selection::Reader, selection: Atom) -> anyhow::Result<Option<Window>> {
+    match reader.owner(selection)? {
+        Some(owner) if reader.window_name(owner)?.as_deref() == Some(WINDOW_NAME) => {
+            Ok(Some(owner))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Signal the fw holding the clipboard to exit, as if it got SIGTERM. Errors out if the clipboard
+/// isn't held by fw.
+pub fn kill() -> anyhow::Result<()> {
+    let reader = selection::Reader::connect()?;
+    let Some(owner) = find(&reader, reader.selection(false)?)? else {
+        bail!("the clipboard isn't held by fw");
+    };
+    let Some(pid) = reader.cardinal(owner, "_NET_WM_PID")? else {
+        bail!("the fw holding the clipboard didn't record its PID");
+    };
+    let pid = libc::pid_t::try_from(pid).context("invalid PID")?;
+    // SAFETY: just a syscall
+    if unsafe { libc::kill(pid, libc::SIGTERM) } < 0 {
+        return Err(io::Error::last_os_error()).with_context(|| format!("failed to signal {pid}"));
+    }
+    Ok(())
+}
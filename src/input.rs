@@ -0,0 +1,193 @@
+//! Reading and normalizing the text to convert.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use encoding_rs::Encoding;
+
+use crate::lossy;
+
+/// How to treat line endings in the input, see `--crlf`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndings {
+    /// Leave `\r\n` and `\n` as they are.
+    Keep,
+    /// Convert `\r\n` to `\n`.
+    Lf,
+    /// Convert every line ending to `\r\n`.
+    Crlf,
+}
+
+impl LineEndings {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "keep" => Some(LineEndings::Keep),
+            "lf" => Some(LineEndings::Lf),
+            "crlf" => Some(LineEndings::Crlf),
+            _ => None,
+        }
+    }
+
+    /// The line ending to print after the output.
+    pub fn terminator(self) -> &'static str {
+        match self {
+            LineEndings::Crlf => "\r\n",
+            _ => "\n",
+        }
+    }
+
+    pub fn apply(self, text: String) -> String {
+        match self {
+            LineEndings::Keep => text,
+            LineEndings::Lf => text.replace("\r\n", "\n"),
+            LineEndings::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Read all of stdin. Unless `keep_trailing_newline` is set, one trailing line ending (`\n` or
+/// `\r\n`) is removed, since that's almost always just the end of `echo` output rather than part
+/// of the message.
+pub fn read_stdin(keep_trailing_newline: bool) -> anyhow::Result<String> {
+    let input = String::from_utf8(read_stdin_bytes()?)
+        .context("stdin is not valid UTF-8 (use --lossy to pass invalid bytes through)")?;
+    Ok(trim_trailing_newline(input, keep_trailing_newline))
+}
+
+/// Like [`read_stdin`], but invalid UTF-8 is decoded with [`lossy::decode`] instead of being an
+/// error.
+pub fn read_stdin_lossy(keep_trailing_newline: bool) -> anyhow::Result<String> {
+    let input = lossy::decode(&read_stdin_bytes()?);
+    Ok(trim_trailing_newline(input, keep_trailing_newline))
+}
+
+/// Like [`read_stdin`], but for input in `encoding` rather than UTF-8, for `--input-encoding`.
+pub fn read_stdin_encoded(
+    encoding: &'static Encoding,
+    keep_trailing_newline: bool,
+) -> anyhow::Result<String> {
+    let bytes = read_stdin_bytes()?;
+    let (input, encoding, had_errors) = encoding.decode(&bytes);
+    if had_errors {
+        bail!("stdin is not valid {}", encoding.name());
+    }
+    Ok(trim_trailing_newline(
+        input.into_owned(),
+        keep_trailing_newline,
+    ))
+}
+
+/// Let people know why we're sitting there doing nothing if stdin is a terminal, which usually
+/// means they forgot to give fw any text.
+pub fn prompt_if_terminal() {
+    if io::stdin().is_terminal() {
+        eprintln!("fw: reading from stdin, Ctrl-D to finish");
+    }
+}
+
+/// Wait up to `timeout` for stdin to have something to read, or to be closed, for
+/// `--stdin-timeout`. This keeps fw from hanging forever when it's run without any text from
+/// somewhere that never closes stdin, like some launchers.
+pub fn wait_for_stdin(timeout: Duration) -> anyhow::Result<()> {
+    let deadline = Instant::now() + timeout;
+    let mut fd = libc::pollfd {
+        fd: io::stdin().as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let millis = libc::c_int::try_from(left.as_millis()).unwrap_or(libc::c_int::MAX);
+        // SAFETY: fd is a single valid pollfd
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            // POLLHUP and friends are in revents too, and reading will tell us about those
+            n if n > 0 => return Ok(()),
+            0 => bail!(
+                "nothing on stdin after {}s, give the text to convert as arguments instead \
+                 (see --help)",
+                timeout.as_secs()
+            ),
+            _ => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err).context("failed to wait for stdin");
+                }
+            }
+        }
+    }
+}
+
+/// Compose the text in `$VISUAL`, `$EDITOR`, or vi, starting from `initial`, and return what was
+/// saved with the trailing newline handled like [`read_stdin`].
+pub fn edit(initial: &str, keep_trailing_newline: bool) -> anyhow::Result<String> {
+    let path = std::env::temp_dir().join(format!("fw-{}.txt", std::process::id()));
+    // nobody else gets to read what we're writing, or swap the file out from under us
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .with_context(|| format!("failed to create '{}'", path.display()))?;
+
+    let result = (|| {
+        if !initial.is_empty() {
+            writeln!(file, "{initial}")
+                .with_context(|| format!("failed to write '{}'", path.display()))?;
+        }
+        drop(file);
+
+        let editor = ["VISUAL", "EDITOR"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .find(|e| !e.is_empty())
+            .unwrap_or_else(|| "vi".into());
+        // through the shell like git does, so that the editor can have arguments like
+        // "code --wait"
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(r#"eval "$FW_EDITOR" '"$@"'"#)
+            .arg("sh")
+            .arg(&path)
+            .env("FW_EDITOR", &editor)
+            .status()
+            .with_context(|| format!("failed to run {}", editor.to_string_lossy()))?;
+        if !status.success() {
+            bail!("{} exited with {status}", editor.to_string_lossy());
+        }
+        let input = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read '{}'", path.display()))?;
+        Ok(trim_trailing_newline(input, keep_trailing_newline))
+    })();
+    let _ = fs::remove_file(&path);
+
+    let input = result?;
+    if input.trim().is_empty() {
+        bail!("nothing to convert, the file was left empty");
+    }
+    Ok(input)
+}
+
+fn read_stdin_bytes() -> anyhow::Result<Vec<u8>> {
+    let mut input = Vec::new();
+    std::io::stdin()
+        .lock()
+        .read_to_end(&mut input)
+        .context("failed to read stdin")?;
+    Ok(input)
+}
+
+fn trim_trailing_newline(mut input: String, keep_trailing_newline: bool) -> String {
+    if !keep_trailing_newline {
+        if input.ends_with("\r\n") {
+            input.truncate(input.len() - 2);
+        } else if input.ends_with('\n') {
+            input.pop();
+        }
+    }
+    input
+}
@@ -0,0 +1,78 @@
+//! Invisible formatting characters that pasted text is full of, for `--strip-invisible`.
+//!
+//! They take up no space on their own, but they still split runs of converted text, and a
+//! directional mark between fullwidth characters can reorder them.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+const ZWJ: char = '\u{200D}';
+
+/// The characters [`strip`] removes, and their names for the report.
+pub const INVISIBLE: [(char, &str); 17] = [
+    ('\u{FEFF}', "BYTE ORDER MARK"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    (ZWJ, "ZERO WIDTH JOINER"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{00AD}', "SOFT HYPHEN"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{061C}', "ARABIC LETTER MARK"),
+    ('\u{202A}', "LEFT-TO-RIGHT EMBEDDING"),
+    ('\u{202B}', "RIGHT-TO-LEFT EMBEDDING"),
+    ('\u{202C}', "POP DIRECTIONAL FORMATTING"),
+    ('\u{202D}', "LEFT-TO-RIGHT OVERRIDE"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{2066}', "LEFT-TO-RIGHT ISOLATE"),
+    ('\u{2067}', "RIGHT-TO-LEFT ISOLATE"),
+    ('\u{2069}', "POP DIRECTIONAL ISOLATE"),
+];
+
+fn is_invisible(c: char) -> bool {
+    INVISIBLE.iter().any(|&(i, _)| i == c)
+}
+
+/// How many of each [`INVISIBLE`] character [`strip`] removed, in the order of that table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Removed([usize; INVISIBLE.len()]);
+
+impl Removed {
+    fn add(&mut self, c: char) {
+        if let Some(i) = INVISIBLE.iter().position(|&(i, _)| i == c) {
+            self.0[i] += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&n| n == 0)
+    }
+
+    /// The characters that were removed, with their names and how many of each.
+    pub fn iter(&self) -> impl Iterator<Item = (char, &'static str, usize)> + '_ {
+        INVISIBLE
+            .iter()
+            .zip(self.0)
+            .filter(|&(_, n)| n > 0)
+            .map(|(&(c, name), n)| (c, name, n))
+    }
+}
+
+/// Remove the [`INVISIBLE`] characters from `text`. A zero width joiner that joins an emoji
+/// sequence like `👨‍👩‍👧` into one cluster is kept, since that one is visible: without it the
+/// family falls apart into three people.
+pub fn strip(text: &str) -> (String, Removed) {
+    let mut out = String::with_capacity(text.len());
+    let mut removed = Removed::default();
+    for g in text.graphemes(true) {
+        for (i, c) in g.char_indices() {
+            // a joiner inside a cluster rather than at either end of one joins an emoji sequence
+            let joins = c == ZWJ && i > 0 && i + c.len_utf8() < g.len();
+            if is_invisible(c) && !joins {
+                removed.add(c);
+            } else {
+                out.push(c);
+            }
+        }
+    }
+    (out, removed)
+}
@@ -0,0 +1,445 @@
+//! Display-width-aware layout of converted text.
+//!
+//! Fullwidth characters take up two terminal columns, so anything that lines text up needs to
+//! measure with [`unicode_width`] rather than counting chars or bytes. Lines are only ever
+//! broken between grapheme clusters, so an emoji sequence or an accented letter is never split.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::ansi::{self, Token};
+use crate::style::{fw_char, WIDE_SPACE};
+
+/// How to expand tab characters, see `--expand-tabs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tabs {
+    /// Replace each tab with this many wide spaces.
+    Fixed(usize),
+    /// Pad to the next multiple of this many columns.
+    Stops(usize),
+}
+
+/// The display width of a single char. Control characters count as zero.
+pub fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// Split `text` into grapheme clusters, but keep ANSI escape sequences whole, so that layout
+/// treats them as a single zero-width unit instead of measuring their printable bytes.
+fn units(text: &str) -> impl Iterator<Item = &str> {
+    ansi::tokens(text).flat_map(|token| {
+        let (escape, text) = match token {
+            Token::Escape(escape) => (Some(escape), ""),
+            Token::Text(text) => (None, text),
+        };
+        escape.into_iter().chain(text.graphemes(true))
+    })
+}
+
+/// The display width of a single grapheme cluster. Control characters, including a `\r\n`
+/// cluster and escape sequences from [`units`], count as zero.
+pub fn grapheme_width(g: &str) -> usize {
+    if g.starts_with(char::is_control) {
+        0
+    } else {
+        g.width()
+    }
+}
+
+/// The line break to add to `text`: `\r\n` if it has CRLF line endings, so that the lines stay
+/// alike, and `\n` otherwise.
+fn newline(text: &str) -> &'static str {
+    if text.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Push `cols` columns of blank space onto `out`, using wide spaces and one halfwidth space if
+/// `cols` is odd.
+pub fn push_blank(out: &mut String, cols: usize) {
+    out.extend(std::iter::repeat_n(WIDE_SPACE, cols / 2));
+    if cols % 2 == 1 {
+        out.push(' ');
+    }
+}
+
+pub fn expand_tabs(text: &str, tabs: Tabs) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut col = 0;
+    for g in units(text) {
+        match g {
+            "\t" => match tabs {
+                Tabs::Fixed(n) => {
+                    out.extend(std::iter::repeat_n(WIDE_SPACE, n));
+                    col += n * 2;
+                }
+                // like Fixed(0), rather than dividing by zero
+                Tabs::Stops(0) => (),
+                Tabs::Stops(width) => {
+                    let pad = width - col % width;
+                    push_blank(&mut out, pad);
+                    col += pad;
+                }
+            },
+            "\n" | "\r\n" => {
+                out.push_str(g);
+                col = 0;
+            }
+            _ => {
+                out.push_str(g);
+                col += grapheme_width(g);
+            }
+        }
+    }
+    out
+}
+
+/// The display width of a string.
+pub fn str_width(s: &str) -> usize {
+    units(s).map(grapheme_width).sum()
+}
+
+fn is_break_space(c: char) -> bool {
+    c == WIDE_SPACE || c == ' '
+}
+
+/// Greedily wrap each line of `text` so that it fits in `cols` columns, breaking at spaces. The
+/// space at a break is dropped. Words that are too long on their own are split wherever needed.
+/// The breaks match the text's line endings, see [`newline`].
+pub fn wrap(text: &str, cols: usize) -> String {
+    let cols = cols.max(2);
+    let newline = newline(text);
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        let end = &line[content.len()..];
+        let mut col = 0;
+        // words keep the space after them, which is dropped if the line breaks there
+        for word in content.split_inclusive(is_break_space) {
+            let (body, space) = match word.chars().next_back() {
+                Some(c) if is_break_space(c) => word.split_at(word.len() - c.len_utf8()),
+                _ => (word, ""),
+            };
+            let body_width = str_width(body);
+            if col > 0 && col + body_width > cols {
+                // drop any trailing spaces from the previous word before breaking
+                while out.ends_with(is_break_space) {
+                    out.pop();
+                }
+                out.push_str(newline);
+                col = 0;
+            }
+            for g in units(body) {
+                let w = grapheme_width(g);
+                if col > 0 && col + w > cols {
+                    out.push_str(newline);
+                    col = 0;
+                }
+                out.push_str(g);
+                col += w;
+            }
+            if !space.is_empty() {
+                let w = str_width(space);
+                if col + w <= cols {
+                    out.push_str(space);
+                    col += w;
+                } else {
+                    out.push_str(newline);
+                    col = 0;
+                }
+            }
+        }
+        out.push_str(end);
+    }
+    out
+}
+
+/// The mark that [`quote`] puts before each line.
+pub const QUOTE: &str = "＞ ";
+
+/// Call `prefix` with each line number of `text`, counting from 1, to add to the start of that
+/// line. A line break at the very end doesn't start another line.
+fn prefix_lines(text: &str, mut prefix: impl FnMut(usize, &mut String)) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let body = text.strip_suffix('\n');
+    for (i, line) in body.unwrap_or(text).split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        prefix(i + 1, &mut out);
+        out.push_str(line);
+    }
+    if body.is_some() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Put the [`QUOTE`] mark before each line of `text`, see `--quote`.
+pub fn quote(text: &str) -> String {
+    prefix_lines(text, |_, out| out.push_str(QUOTE))
+}
+
+/// Number each line of `text` with fullwidth digits and a wide space, see `--number`. The numbers
+/// are right-aligned so that the lines after them still line up.
+pub fn number(text: &str) -> String {
+    let lines = text.strip_suffix('\n').unwrap_or(text).split('\n').count();
+    let digits = lines.to_string().len();
+    prefix_lines(text, |n, out| {
+        let n = n.to_string();
+        out.extend(std::iter::repeat_n(WIDE_SPACE, digits - n.len()));
+        out.extend(n.chars().map(fw_char));
+        out.push(WIDE_SPACE);
+    })
+}
+
+/// Whether `line` has both halfwidth and fullwidth characters, which only line up in fonts where
+/// a fullwidth character is exactly two halfwidth ones wide. Spaces don't count, since [`pad`]
+/// needs a halfwidth one for an odd number of columns.
+pub fn mixes_widths(line: &str) -> bool {
+    let (mut half, mut full) = (false, false);
+    for g in units(line) {
+        match grapheme_width(g) {
+            1 if g != " " => half = true,
+            2 if !g.starts_with(WIDE_SPACE) => full = true,
+            _ => (),
+        }
+    }
+    half && full
+}
+
+/// Horizontal alignment for [`pad`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Align::Left),
+            "center" => Some(Align::Center),
+            "right" => Some(Align::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Cut `line` down to at most `cols` columns, returning the kept prefix and its width.
+pub fn truncate_line(line: &str, cols: usize) -> (&str, usize) {
+    let mut width = 0;
+    for g in units(line) {
+        let i = g.as_ptr() as usize - line.as_ptr() as usize;
+        let w = grapheme_width(g);
+        if width + w > cols {
+            return (&line[..i], width);
+        }
+        width += w;
+    }
+    (line, width)
+}
+
+/// Pad or truncate every line of `text` to exactly `cols` columns.
+pub fn pad(text: &str, cols: usize, align: Align) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        // pad before the \r of a CRLF line ending rather than after it
+        let (line, cr) = match line.strip_suffix('\r') {
+            Some(line) => (line, "\r"),
+            None => (line, ""),
+        };
+        let (line, width) = truncate_line(line, cols);
+        let extra = cols - width;
+        let (left, right) = match align {
+            Align::Left => (0, extra),
+            Align::Center => (extra / 2, extra - extra / 2),
+            Align::Right => (extra, 0),
+        };
+        push_blank(&mut out, left);
+        out.push_str(line);
+        push_blank(&mut out, right);
+        out.push_str(cr);
+    }
+    out
+}
+
+/// How much text [`truncate`] keeps, see `--max-chars` and `--max-width`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// This many grapheme clusters.
+    Chars(usize),
+    /// This many terminal columns.
+    Width(usize),
+}
+
+impl Limit {
+    fn measure(self, g: &str) -> usize {
+        match self {
+            // escape sequences and line breaks don't count
+            Limit::Chars(_) => usize::from(!g.starts_with(char::is_control)),
+            Limit::Width(_) => grapheme_width(g),
+        }
+    }
+
+    fn measure_str(self, s: &str) -> usize {
+        units(s).map(|g| self.measure(g)).sum()
+    }
+
+    fn get(self) -> usize {
+        match self {
+            Limit::Chars(n) | Limit::Width(n) => n,
+        }
+    }
+}
+
+/// Which part of the text [`truncate`] cuts out, see `--truncate-mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncate {
+    End,
+    Middle,
+    Start,
+}
+
+impl Truncate {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "end" => Some(Truncate::End),
+            "middle" => Some(Truncate::Middle),
+            "start" => Some(Truncate::Start),
+            _ => None,
+        }
+    }
+}
+
+/// Cut `text` down to `limit` as a whole, putting `ellipsis` where the text was cut. Text that
+/// already fits is returned as-is, and if `ellipsis` alone doesn't fit, that's all that's left,
+/// cut down to the limit itself. Escape sequences in the part that's cut are kept, so that colors
+/// are still reset.
+pub fn truncate(text: &str, limit: Limit, mode: Truncate, ellipsis: &str) -> String {
+    if limit.measure_str(text) <= limit.get() {
+        return text.to_owned();
+    }
+    // how many units fit in a budget, stopping at the first one that doesn't
+    let take = |budget: &mut usize, g: &str| match budget.checked_sub(limit.measure(g)) {
+        Some(left) => {
+            *budget = left;
+            true
+        }
+        None => false,
+    };
+    let mut budget = limit.get();
+    let ellipsis: String = units(ellipsis)
+        .take_while(|g| take(&mut budget, g))
+        .collect();
+    let (mut head, mut tail) = match mode {
+        Truncate::End => (budget, 0),
+        Truncate::Middle => (budget - budget / 2, budget / 2),
+        Truncate::Start => (0, budget),
+    };
+
+    let units: Vec<&str> = units(text).collect();
+    let head_len = units.iter().take_while(|g| take(&mut head, g)).count();
+    let tail_len = units[head_len..]
+        .iter()
+        .rev()
+        .take_while(|g| take(&mut tail, g))
+        .count();
+    let cut = head_len..units.len() - tail_len;
+
+    let mut out = String::with_capacity(text.len());
+    out.extend(units[..cut.start].iter().copied());
+    out.push_str(&ellipsis);
+    out.extend(
+        units[cut.clone()]
+            .iter()
+            .filter(|g| g.starts_with('\x1b'))
+            .copied(),
+    );
+    out.extend(units[cut.end..].iter().copied());
+    out
+}
+
+/// The characters used to draw a box with [`draw_box`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxChars {
+    pub top_left: char,
+    pub horizontal: char,
+    pub top_right: char,
+    pub vertical: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+}
+
+impl BoxChars {
+    pub const STYLES: [(&'static str, &'static str); 6] = [
+        ("light", "┌─┐│└┘"),
+        ("heavy", "┏━┓┃┗┛"),
+        ("double", "╔═╗║╚╝"),
+        ("rounded", "╭─╮│╰╯"),
+        ("ascii", "+-+|++"),
+        ("sparkle", "✨✨✨✨✨✨"),
+    ];
+
+    /// Parse a `--box` value, either one of the [`STYLES`](Self::STYLES) names or six characters
+    /// in the order top-left, horizontal, top-right, vertical, bottom-left, bottom-right.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        let chars = Self::STYLES
+            .iter()
+            .find(|(name, _)| *name == spec)
+            .map_or(spec, |(_, chars)| chars);
+        let chars: Vec<char> = chars.chars().collect();
+        let [top_left, horizontal, top_right, vertical, bottom_left, bottom_right] = chars[..]
+        else {
+            return None;
+        };
+        Some(Self {
+            top_left,
+            horizontal,
+            top_right,
+            vertical,
+            bottom_left,
+            bottom_right,
+        })
+    }
+}
+
+/// Draw a box around `text`, with one wide space of padding inside each side. The rows end with
+/// the text's line endings, see [`newline`].
+pub fn draw_box(text: &str, chars: &BoxChars) -> String {
+    let newline = newline(text);
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let content_width = lines.iter().copied().map(str_width).max().unwrap_or(0);
+    // round the interior up so that the horizontal edges tile it exactly
+    let edge_width = char_width(chars.horizontal).max(1);
+    let inner = (content_width + 4).div_ceil(edge_width) * edge_width;
+    let edge: String = std::iter::repeat_n(chars.horizontal, inner / edge_width).collect();
+
+    let mut out = String::with_capacity(text.len() * 2);
+    out.push(chars.top_left);
+    out.push_str(&edge);
+    out.push(chars.top_right);
+    for line in lines {
+        out.push_str(newline);
+        out.push(chars.vertical);
+        out.push(WIDE_SPACE);
+        out.push_str(&pad(line, inner - 4, Align::Left));
+        out.push(WIDE_SPACE);
+        out.push(chars.vertical);
+    }
+    out.push_str(newline);
+    out.push(chars.bottom_left);
+    out.push_str(&edge);
+    out.push(chars.bottom_right);
+    out
+}
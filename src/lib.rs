@@ -0,0 +1,22 @@
+//! The conversion core of fw: text styles and display-width-aware layout.
+//!
+//! Everything that touches the clipboard, the terminal, or the filesystem lives in the `fw`
+//! binary instead.
+
+pub mod ansi;
+pub mod bidi;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod case;
+pub mod convert;
+pub mod emoji;
+pub mod format;
+pub mod invisible;
+pub mod layout;
+pub mod protect;
+pub mod roundtrip;
+pub mod style;
+pub mod template;
+pub mod urls;
+#[cfg(feature = "wasm")]
+pub mod wasm;
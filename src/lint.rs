@@ -0,0 +1,88 @@
+//! `fw lint`, checking that text will line up in a monospace layout.
+
+use std::fs;
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use fw::layout::{self, Align};
+
+use crate::{cli, input};
+
+pub fn command() -> Command {
+    Command::new("lint")
+        .about("Check that text lines up in a monospace layout")
+        .long_about(
+            "Check that text lines up in a monospace layout, like ASCII art or a table. Lines \
+             wider than --columns are reported, and so are lines that mix halfwidth and \
+             fullwidth characters, since those only line up in fonts where a fullwidth \
+             character is exactly two halfwidth ones wide. Problems are printed and make the \
+             command fail.",
+        )
+        .arg(
+            Arg::new("columns")
+                .short('c')
+                .long("columns")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .required(true)
+                .help("The widest a line may be, in display columns"),
+        )
+        .arg(
+            cli::style_arg()
+                .default_value(None::<&str>)
+                .env(None::<&str>)
+                .help("Check the text converted to this style instead of the text itself"),
+        )
+        .arg(cli::seed_arg())
+        .arg(cli::leet_level_arg())
+        .arg(cli::morse_symbols_arg())
+        .arg(Arg::new("fix").long("fix").action(ArgAction::SetTrue).help(
+            "Print the text with every line padded to exactly --columns columns, \
+                       cutting off lines that are too wide. Mixed widths can't be fixed this \
+                       way, so those are still reported and make the command fail",
+        ))
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Read the text from FILE instead of stdin"),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let mut text = match args.get_one::<String>("file") {
+        Some(path) => fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?,
+        None => input::read_stdin(false)?,
+    };
+    if args.contains_id("style") {
+        text = cli::style_from_args(args).apply(&text);
+    }
+    let cols = *args.get_one::<usize>("columns").unwrap();
+    let fix = args.get_flag("fix");
+
+    let mut problems = 0;
+    for (i, line) in text.lines().enumerate() {
+        let n = i + 1;
+        let width = layout::str_width(line);
+        if width > cols && !fix {
+            println!("line {n}: {width} columns wide, over the limit of {cols}");
+            problems += 1;
+        }
+        if layout::mixes_widths(line) {
+            let problem = format!("line {n}: mixes halfwidth and fullwidth characters");
+            if fix {
+                tracing::warn!("{problem}");
+            } else {
+                println!("{problem}");
+            }
+            problems += 1;
+        }
+    }
+    if fix {
+        println!("{}", layout::pad(&text, cols, Align::Left));
+    }
+    match problems {
+        0 => Ok(()),
+        1 => bail!("1 problem found"),
+        n => bail!("{n} problems found"),
+    }
+}
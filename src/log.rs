@@ -0,0 +1,133 @@
+//! Diagnostics through `tracing`, as plain messages or JSON lines, see `--log-format`.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::process::Command as Process;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{anyhow, Context};
+use clap::{Arg, ArgMatches, Command};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Add the logging arguments to `cmd`, for it and all of its subcommands.
+pub fn add_args(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("log-format")
+            .long("log-format")
+            .value_name("FORMAT")
+            .value_parser(["pretty", "json"])
+            .default_value("pretty")
+            .env("FW_LOG_FORMAT")
+            .global(true)
+            .help(
+                "How to write errors and other messages: 'pretty' for people, or 'json' for \
+                   one JSON object per line",
+            ),
+    )
+    .arg(
+        Arg::new("log-file")
+            .long("log-file")
+            .value_name("PATH")
+            .value_parser(clap::value_parser!(PathBuf))
+            .env("FW_LOG_FILE")
+            .global(true)
+            .help("Append messages to PATH instead of writing them to stderr"),
+    )
+}
+
+/// The `--log-format` and `--log-file` that [`init`] set up, for [`pass_on`].
+static SETTINGS: OnceLock<(String, Option<PathBuf>)> = OnceLock::new();
+
+/// The `pretty` format, which looks like fw always has. Messages that end up in a file get a
+/// timestamp and the PID, since several fws may be writing to it.
+struct Pretty {
+    timestamps: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for Pretty
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let level = *event.metadata().level();
+        if self.timestamps {
+            SystemTime.format_time(&mut writer)?;
+            write!(writer, " fw[{}]: ", std::process::id())?;
+            match level {
+                Level::ERROR => write!(writer, "error: ")?,
+                Level::WARN => write!(writer, "warning: ")?,
+                _ => (),
+            }
+        } else {
+            match level {
+                Level::ERROR => write!(writer, "Error: ")?,
+                Level::WARN => write!(writer, "fw: warning: ")?,
+                _ => write!(writer, "fw: ")?,
+            }
+        }
+        ctx.format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Send messages where the [`add_args`] arguments in `args` say. `to_file` is whether stderr is
+/// a log file already, like for a daemonized clipboard holder.
+pub fn init(args: &ArgMatches, to_file: bool) -> anyhow::Result<()> {
+    let format = args.get_one::<String>("log-format").cloned();
+    let path = args.get_one::<PathBuf>("log-file").cloned();
+    let writer = match &path {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("failed to open log file '{}'", path.display()))?;
+            BoxMakeWriter::new(Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let to_file = to_file || path.is_some();
+    let builder = tracing_subscriber::fmt().with_writer(writer);
+    match format.as_deref() {
+        Some("json") => builder.json().try_init(),
+        _ => builder
+            .event_format(Pretty {
+                timestamps: to_file,
+            })
+            .try_init(),
+    }
+    .map_err(|err| anyhow!(err))
+    .context("failed to set up logging")?;
+    let _ = SETTINGS.set((format.unwrap_or_default(), path));
+    Ok(())
+}
+
+/// Give a child fw the same logging settings as this one.
+pub fn pass_on(cmd: &mut Process) {
+    if let Some((format, path)) = SETTINGS.get() {
+        cmd.arg(format!("--log-format={format}"));
+        if let Some(path) = path {
+            cmd.arg("--log-file").arg(path);
+        }
+    }
+}
+
+/// Make sure that messages go somewhere, for errors from before [`init`] was called.
+pub fn init_default() {
+    let _ = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .event_format(Pretty { timestamps: false })
+        .try_init();
+}
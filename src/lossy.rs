@@ -0,0 +1,59 @@
+//! Passing invalid UTF-8 through a conversion untouched, for `--lossy`.
+//!
+//! Each invalid byte is decoded to a placeholder char in the last private use plane, which the
+//! styles leave alone and layout counts as one column, and encoded back to the original byte when
+//! writing the output. A real U+10FF00..U+10FFFF in the input comes out as a raw byte too, but
+//! nobody puts those in text they're converting.
+
+use std::borrow::Cow;
+
+const PLACEHOLDER_BASE: u32 = 0x10FF00;
+
+fn placeholder_byte(c: char) -> Option<u8> {
+    (c as u32)
+        .checked_sub(PLACEHOLDER_BASE)
+        .and_then(|b| u8::try_from(b).ok())
+}
+
+/// Decode `bytes` as UTF-8, replacing each invalid byte with its placeholder.
+pub fn decode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        out.push_str(chunk.valid());
+        for &b in chunk.invalid() {
+            out.push(char::from_u32(PLACEHOLDER_BASE + b as u32).unwrap());
+        }
+    }
+    out
+}
+
+/// Encode `text` to UTF-8, turning placeholders back into the original bytes.
+pub fn encode(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(|c| placeholder_byte(c).is_some()) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+    let mut out = Vec::with_capacity(text.len());
+    let mut buf = [0; 4];
+    for c in text.chars() {
+        match placeholder_byte(c) {
+            Some(b) => out.push(b),
+            None => out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Replace placeholders with U+FFFD, for places like the clipboard that can only take valid text.
+pub fn replace(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| placeholder_byte(c).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.chars()
+            .map(|c| match placeholder_byte(c) {
+                Some(_) => char::REPLACEMENT_CHARACTER,
+                None => c,
+            })
+            .collect(),
+    )
+}
@@ -1,79 +1,65 @@
-use std::io::Read;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
 
-use anyhow::Context;
-use arboard::{Clipboard, SetExtLinux};
-use clap::{Arg, ArgAction, ArgGroup};
+use anyhow::{bail, Context};
+use clap::builder::{NonEmptyStringValueParser, PossibleValuesParser};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, ColorChoice};
+use encoding_rs::Encoding;
+use fw::bidi;
+use fw::case::Case;
+use fw::convert::{self, Converted, Options};
+use fw::format::Format;
+use fw::layout::{Align, BoxChars, Limit, Tabs, Truncate};
+use fw::protect::Protect;
+use fw::style::{Chain, Classes, Digits, Style};
 
-const WIDE_SPACE: char = '\u{3000}';
-const FULLWIDTH_OFFSET: u32 = 0xFEE0;
+use config::{Config, Decoration};
+use input::LineEndings;
 
-fn fw_char(c: char) -> char {
-    match c {
-        ' ' => WIDE_SPACE,
-        '!'..='~' => char::from_u32((c as u32) + FULLWIDTH_OFFSET).unwrap(),
-        _ => c,
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum WaitMode {
-    NoWait,
-    Foreground,
-    Background,
-}
-
-fn set_clipboard(text: &str, wait: WaitMode) -> anyhow::Result<()> {
-    /// Inner function to do *all* of the clipboard stuff, but without any fork shennanigans. This
-    /// may run in the main parent or child process.
-    fn inner(text: &str, wait: bool) -> anyhow::Result<()> {
-        let mut cb = Clipboard::new().context("failed to init clipboard")?;
-        let mut set = cb.set();
-        if wait {
-            set = set.wait();
-        }
-        set.text(text).context("failed to set clipboard contents")
-    }
-
-    match wait {
-        WaitMode::NoWait => inner(text, false),
-        WaitMode::Foreground => inner(text, true),
-        WaitMode::Background => {
-            // Fork to the background, then set the clipboard and wait in the background process.
-            // The parent will return Ok immediately unless fork failed.
-            //
-            // This is just a single fork and then disown, we don't do setsid() and double-fork
-            // like a "proper" daemon, because it doesn't seem necessary. We also keep stdio open
-            // so we can print errors if needed.
-            //
-            // SAFETY: "After a fork() in a multithreaded program, the child can safely call only
-            // async-signal-safe functions until it calls execve(2)". This translates to: we MUST
-            // fork only when the process is single-threaded. Specifically, we MUST NOT initialize
-            // or touch any of the clipboard handling in the parent and then use it from the child,
-            // because arboard spawns a helper thread for X11 clipboard handling. As long as we're
-            // single threaded at this point, the fork is safe, and all subsequent threads are only
-            // used in the context of the child process.
-            match unsafe { libc::fork() } {
-                // fork failed
-                -1 => Err(std::io::Error::last_os_error()).context("fork failed"),
-
-                // child process, set the clipboard and exit.
-                0 => {
-                    let retcode = match inner(text, true) {
-                        Ok(()) => 0,
-                        Err(err) => {
-                            eprintln!("fw clipboard error: {err:#}");
-                            1
-                        }
-                    };
-                    std::process::exit(retcode);
-                }
-
-                // parent process, return success immediately, implicitly disown the child.
-                _child_pid => Ok(()),
-            }
-        }
-    }
-}
+mod banner;
+mod batch;
+#[cfg(feature = "bench")]
+mod bench;
+mod chain;
+mod cli;
+mod clipboard;
+mod config;
+mod decorate;
+mod detect;
+mod history;
+mod holder;
+mod input;
+mod lint;
+mod log;
+mod lossy;
+mod manager;
+mod paste;
+mod paths;
+mod picker;
+mod profile;
+mod repl;
+mod retry;
+mod roundtrip_check;
+mod selection;
+#[cfg(feature = "self-update")]
+mod self_update;
+#[cfg(feature = "http")]
+mod serve_http;
+mod signals;
+mod status;
+mod stream;
+mod styles;
+mod table;
+#[cfg(feature = "targets")]
+mod targets;
+#[cfg(feature = "tui")]
+mod tui;
 
 fn env_is_nonempty(var: &str) -> bool {
     match std::env::var_os(var) {
@@ -83,33 +69,528 @@ fn env_is_nonempty(var: &str) -> bool {
 }
 
 fn run() -> anyhow::Result<()> {
-    let args = clap::command!()
+    let cmd = log::add_args(clipboard::add_args(clap::command!()))
         .about("Convert text to fullwidth glyphs (for cate memes)")
+        .color(if env_is_nonempty("NO_COLOR") {
+            ColorChoice::Never
+        } else {
+            ColorChoice::Auto
+        })
+        .arg(
+            cli::style_arg()
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("Which style to convert the text to, built-in or from the config file"),
+        )
+        .arg(cli::seed_arg())
+        .arg(cli::leet_level_arg())
+        .arg(cli::morse_symbols_arg())
+        .arg(
+            Arg::new("decode")
+                .long("decode")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "cjk-punct",
+                    "emoji",
+                    "case",
+                    "leet",
+                    "digits",
+                    "decorate",
+                    "interactive",
+                ])
+                .help("Convert text in the --style back to plain ASCII instead"),
+        )
+        .arg(
+            Arg::new("decode-first")
+                .long("decode-first")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["decode", "skip-converted"])
+                .help(
+                    "Turn fullwidth characters (and ones already in the --style) in the input \
+                       back into ASCII before converting, so that partly converted text comes \
+                       out uniformly",
+                ),
+        )
+        .arg(
+            Arg::new("skip-converted")
+                .long("skip-converted")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("decode")
+                .help(
+                    "Leave runs of fullwidth characters (and ones already in the --style) in \
+                       the input exactly as they are, converting only the rest",
+                ),
+        )
+        .arg(
+            Arg::new("only")
+                .long("only")
+                .value_name("CLASSES")
+                .value_parser(Classes::parse)
+                .conflicts_with("except")
+                .help(
+                    "Only convert these comma-separated classes of characters: letters, \
+                       digits, punct, space",
+                ),
+        )
+        .arg(
+            Arg::new("except")
+                .long("except")
+                .value_name("CLASSES")
+                .value_parser(Classes::parse)
+                .help("Convert everything except these classes of characters, see --only"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .action(ArgAction::SetTrue)
+                .help("Print more about what was done to the text to stderr"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .env("FW_CONFIG")
+                .help("Read the config file from FILE instead of $XDG_CONFIG_HOME/fw/config.toml"),
+        )
+        .arg(
+            Arg::new("profile")
+                .short('p')
+                .long("profile")
+                .value_name("NAME")
+                .env("FW_PROFILE")
+                .help(
+                    "Use the options in the config file's profile NAME, like a style and limits \
+                       for a particular app. Options on the command line win over the profile's",
+                ),
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .action(ArgAction::SetTrue)
+                .help("Record this conversion in the history, see 'fw history'"),
+        )
+        .arg(
+            Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("text")
+                .help("Read lines interactively, converting and copying each one"),
+        )
+        .arg(
+            Arg::new("kill")
+                .long("kill")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["text", "interactive"])
+                .help(
+                    "Tell the fw holding the clipboard in the background to stop, which puts \
+                       back the previous contents if it was started with --restore",
+                ),
+        )
+        .arg(
+            Arg::new("edit")
+                .short('e')
+                .long("edit")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["interactive", "lossy", "each"])
+                .help(
+                    "Compose the text to convert in $VISUAL or $EDITOR instead of reading \
+                       stdin. Any text arguments are filled in to start with",
+                ),
+        )
+        .arg(
+            Arg::new("cjk-punct")
+                .long("cjk-punct")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Use CJK punctuation like 。、「」 instead of fullwidth forms of the ASCII \
+                       punctuation",
+                ),
+        )
+        .arg(
+            Arg::new("emoji")
+                .long("emoji")
+                .action(ArgAction::SetTrue)
+                .help("Expand :shortcode: sequences like :cat: and :fire: to emoji"),
+        )
+        .arg(
+            Arg::new("leet")
+                .long("leet")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Speak leet at the --leet-level before converting to the --style, e.g. \
+                       ｃ４７３ with the default fullwidth",
+                ),
+        )
+        .arg(
+            Arg::new("chaos")
+                .long("chaos")
+                .value_name("SEED")
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_parser(clap::value_parser!(u64))
+                .conflicts_with_all(["decode", "decode-first", "skip-converted"])
+                .help(
+                    "Convert each character to a random one of the --chaos-styles instead of \
+                       the --style. The picks are made from SEED, or --seed, so that the same \
+                       seed always gives the same mix",
+                ),
+        )
+        .arg(
+            Arg::new("chaos-styles")
+                .long("chaos-styles")
+                .value_name("STYLES")
+                .value_parser(cli::parse_mixable_styles)
+                .default_value("fullwidth,circled,math-bold")
+                .help("The comma-separated styles for --chaos to pick from"),
+        )
+        .arg(
+            Arg::new("digits")
+                .long("digits")
+                .value_name("DIGITS")
+                .value_parser(Digits::NAMES)
+                .help(
+                    "Write digits as ascii, fullwidth, circled (①②③), or keycap emoji (1️⃣2️⃣3️⃣), \
+                       whatever the --style does with the rest",
+                ),
+        )
+        .arg(
+            Arg::new("case")
+                .long("case")
+                .value_name("CASE")
+                .value_parser(Case::NAMES)
+                .help(
+                    "Change the case of the text before converting it: upper, lower, title \
+                       (capitalize each word), or random",
+                ),
+        )
+        .arg(
+            Arg::new("bidi")
+                .long("bidi")
+                .value_name("MODE")
+                .value_parser(["isolate", "warn", "ignore"])
+                .default_value("ignore")
+                .help(
+                    "What to do about right-to-left text like Hebrew or Arabic, which can be \
+                       reordered by converting the text around it: isolate each right-to-left \
+                       run with FSI and PDI marks, warn about it, or ignore it",
+                ),
+        )
+        .arg(
+            Arg::new("decorate")
+                .short('d')
+                .long("decorate")
+                .value_name("NAME")
+                .help(
+                    "Add a kaomoji/sparkle decoration around the output. Built-in: sparkle, \
+                       stars, cate, uwu, shrug, tableflip, lenny. More can be defined in the \
+                       config file",
+                ),
+        )
+        .arg(Arg::new("prefix").long("prefix").value_name("STR").help(
+            "Add STR before the output as-is, without converting it. The default is \
+                       the config file's prefix",
+        ))
+        .arg(Arg::new("suffix").long("suffix").value_name("STR").help(
+            "Add STR after the output as-is, without converting it. The default is \
+                       the config file's suffix",
+        ))
+        .arg(
+            Arg::new("wrap-with")
+                .long("wrap-with")
+                .value_name("STR")
+                .conflicts_with_all(["prefix", "suffix"])
+                .help("Add STR both before and after the output, like --prefix STR --suffix STR"),
+        )
+        .arg(
+            Arg::new("repeat")
+                .long("repeat")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .help("Output the converted text N times, separated by --repeat-separator"),
+        )
+        .arg(
+            Arg::new("repeat-separator")
+                .long("repeat-separator")
+                .value_name("STR")
+                .requires("repeat")
+                .help(
+                    "What to put between the copies from --repeat as-is. The default is a newline",
+                ),
+        )
+        .arg(
+            Arg::new("keep-trailing-newline")
+                .long("keep-trailing-newline")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Don't strip the trailing newline from stdin, and don't add one when \
+                       printing the output",
+                ),
+        )
+        .arg(
+            Arg::new("crlf")
+                .long("crlf")
+                .value_name("MODE")
+                .value_parser(["keep", "lf", "crlf"])
+                .default_value("keep")
+                .help("Keep line endings as they are, or convert them all to LF or CRLF"),
+        )
+        .arg(
+            Arg::new("each")
+                .long("each")
+                .action(ArgAction::SetTrue)
+                .requires("text")
+                .help(
+                    "Convert each argument separately and print each one on its own line, \
+                       rather than joining them with spaces",
+                ),
+        )
+        .arg(
+            Arg::new("copy-index")
+                .long("copy-index")
+                .value_name("WHICH")
+                .value_parser(CopyIndex::parse)
+                .requires("each")
+                .help(
+                    "With --each, which converted text to copy: the Nth one counting from 1, \
+                       the last one, or all of them one per line (the default)",
+                ),
+        )
+        .arg(
+            Arg::new("tee")
+                .long("tee")
+                .value_name("TEMPLATE")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("{orig}\\n{out}")
+                .help(
+                    "Print the original text along with the converted text, as TEMPLATE \
+                       with {orig} and {out} in it and \\n for a line break. Only the \
+                       converted text is copied",
+                ),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Only convert the parts of the text inside {{...}} placeholders, leaving \
+                       the rest as typed",
+                ),
+        )
+        .arg(
+            Arg::new("invert")
+                .long("invert")
+                .action(ArgAction::SetTrue)
+                .requires("template")
+                .help("With --template, convert everything except the placeholders instead"),
+        )
+        .arg(
+            Arg::new("keep-urls")
+                .long("keep-urls")
+                .action(ArgAction::SetTrue)
+                .help("Leave URLs and email addresses unconverted so that they stay clickable"),
+        )
+        .arg(
+            Arg::new("protect-file")
+                .long("protect-file")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .env("FW_PROTECT_FILE")
+                .help(
+                    "Leave the words and phrases in FILE, one per line, unconverted wherever \
+                       they appear, ignoring case. Lines with * or ? are patterns for whole \
+                       words, like '@*'. Replaces the config file's protect list",
+                ),
+        )
+        .arg(
+            Arg::new("strip-invisible")
+                .long("strip-invisible")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Remove byte order marks, zero-width spaces and joiners, and directional \
+                       marks from the input, except the joiners inside emoji sequences. -v \
+                       reports what was removed",
+                ),
+        )
+        .arg(
+            Arg::new("strip-ansi")
+                .long("strip-ansi")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Remove ANSI escape sequences like colors from the input. Otherwise they're \
+                       passed through unconverted",
+                ),
+        )
+        .arg(
+            Arg::new("lossy")
+                .long("lossy")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Pass invalid UTF-8 in stdin through to the output untouched instead of \
+                       failing. The clipboard gets U+FFFD in place of each invalid byte",
+                ),
+        )
+        .arg(
+            Arg::new("input-encoding")
+                .long("input-encoding")
+                .value_name("ENC")
+                .value_parser(|label: &str| {
+                    Encoding::for_label(label.as_bytes())
+                        .ok_or("expected an encoding like shift_jis, euc-jp, latin1, or utf-16le")
+                })
+                .conflicts_with_all(["lossy", "text", "edit"])
+                .help(
+                    "Read stdin as ENC instead of UTF-8, e.g. shift_jis, euc-jp, latin1, or \
+                       utf-16le. A byte order mark overrides it",
+                ),
+        )
+        .arg(
+            Arg::new("stdin-timeout")
+                .long("stdin-timeout")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64))
+                .env("FW_STDIN_TIMEOUT")
+                .help(
+                    "Fail if nothing arrives on stdin within SECONDS, rather than waiting for \
+                       it forever. Ignored when the text is given as arguments",
+                ),
+        )
+        .arg(
+            Arg::new("expand-tabs")
+                .long("expand-tabs")
+                .value_name("N")
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_parser(clap::value_parser!(usize))
+                .help(
+                    "Expand tabs to N wide spaces, or if N is omitted, to the next 8-column \
+                       tab stop",
+                ),
+        )
         .arg(
-            Arg::new("no-clipboard")
-                .short('n')
-                .long("no-clipboard")
+            Arg::new("wrap")
+                .long("wrap")
+                .value_name("COLS")
+                .value_parser(clap::value_parser!(usize))
+                .help("Wrap the output at spaces so that each line fits in COLS terminal columns"),
+        )
+        .arg(
+            Arg::new("quote")
+                .long("quote")
                 .action(ArgAction::SetTrue)
-                .help("Don't copy the output to the system clipboard"),
+                .help("Start each output line with '＞ ', like a quoted reply"),
         )
         .arg(
-            Arg::new("no-wait")
-                .short('W')
-                .long("no-wait")
+            Arg::new("number")
+                .long("number")
                 .action(ArgAction::SetTrue)
+                .help("Number the output lines with fullwidth digits"),
+        )
+        .arg(
+            Arg::new("pad")
+                .long("pad")
+                .value_name("COLS")
+                .value_parser(clap::value_parser!(usize))
+                .help("Pad or truncate every output line to exactly COLS terminal columns"),
+        )
+        .arg(
+            Arg::new("align")
+                .long("align")
+                .value_name("ALIGN")
+                .value_parser(["left", "center", "right"])
+                .default_value("left")
+                .requires("pad")
+                .help("Where to put the text within the --pad width"),
+        )
+        .arg(
+            Arg::new("box")
+                .long("box")
+                .value_name("STYLE")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("light")
+                .value_parser(|spec: &str| {
+                    BoxChars::from_spec(spec).ok_or_else(|| {
+                        let names: Vec<&str> = BoxChars::STYLES.iter().map(|s| s.0).collect();
+                        format!("expected one of {} or six characters", names.join(", "))
+                    })
+                })
                 .help(
-                    "Don't wait for the clipboard to be reset before exiting. (This is the \
-                       default if a desktop session is detected)",
+                    "Draw a box around the output. STYLE is light (the default), heavy, \
+                       double, rounded, ascii, sparkle, or six characters for the top-left, \
+                       horizontal, top-right, vertical, bottom-left, and bottom-right",
                 ),
         )
         .arg(
-            Arg::new("foreground-wait")
-                .short('F')
-                .long("foreground-wait")
+            Arg::new("max-chars")
+                .long("max-chars")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with("max-width")
+                .help("Cut the output down to N characters, ending with the --ellipsis"),
+        )
+        .arg(
+            Arg::new("max-width")
+                .long("max-width")
+                .value_name("COLS")
+                .value_parser(clap::value_parser!(usize))
+                .help(
+                    "Cut the output down to COLS terminal columns, ending with the --ellipsis. \
+                       Fullwidth characters take two",
+                ),
+        )
+        .arg(
+            Arg::new("truncate-mode")
+                .long("truncate-mode")
+                .value_name("MODE")
+                .value_parser(["end", "middle", "start"])
+                .default_value("end")
+                .help("Which part of the text --max-chars and --max-width cut out"),
+        )
+        .arg(
+            Arg::new("ellipsis")
+                .long("ellipsis")
+                .value_name("STR")
+                .default_value("…")
+                .help("What to put where --max-chars or --max-width cut the text, e.g. '．．．'"),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help(
+                    "Convert large inputs from stdin with N threads. The default is one per \
+                       CPU",
+                ),
+        )
+        .arg(
+            Arg::new("benchmark")
+                .long("benchmark")
                 .action(ArgAction::SetTrue)
+                .hide(true)
+                .help("Print how long the conversion took to stderr"),
+        )
+        .arg(
+            Arg::new(clipboard::HOLDER_ARG)
+                .long(clipboard::HOLDER_ARG)
+                .value_name("LEN")
+                .value_parser(clap::value_parser!(usize))
+                .hide(true)
+                .help("Hold the clipboard in the background, see clipboard::run_holder"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(Format::ALL.map(Format::name)))
+                .default_value("text")
                 .help(
-                    "Wait for the clipboard to be reset in the foreground rather than \
-                       forking to the background",
+                    "Output the converted text as-is, with \\u{...} escapes, with HTML \
+                       entities, or as a JSON string",
                 ),
         )
         .arg(
@@ -121,63 +602,487 @@ fn run() -> anyhow::Result<()> {
                       Omit to read stdin instead.",
                 ),
         )
-        .group(
-            // our clipboard arguments are multually-exclusive
-            ArgGroup::new("clipboard-args")
-                .args(["no-clipboard", "no-wait", "foreground-wait"])
-                .required(false)
-                .multiple(false),
-        )
-        .get_matches();
+        .subcommand(banner::command())
+        .subcommand(batch::command())
+        .subcommand(history::command())
+        .subcommand(lint::command())
+        .subcommand(paste::command())
+        .subcommand(roundtrip_check::command())
+        .subcommand(status::command())
+        .subcommand(styles::command())
+        .subcommand(table::command())
+        .args_conflicts_with_subcommands(true)
+        // so that the command line can override options from a --profile
+        .args_override_self(true);
+    #[cfg(feature = "bench")]
+    let cmd = cmd.subcommand(bench::command());
+    #[cfg(feature = "http")]
+    let cmd = cmd.subcommand(serve_http::command());
+    #[cfg(feature = "self-update")]
+    let cmd = cmd.subcommand(self_update::command());
+    #[cfg(feature = "targets")]
+    let cmd = cmd.subcommand(targets::command());
+    #[cfg(feature = "tui")]
+    let cmd = cmd.subcommand(tui::command());
+    let args = cmd.clone().get_matches();
+    let args = match args.get_one::<String>("profile") {
+        Some(name) => {
+            let config = Config::load(args.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+            profile::matches(cmd, &config, name)?
+        }
+        None => args,
+    };
+    // a daemonized holder's stderr is its log file
+    log::init(&args, args.get_flag("daemonize"))?;
+
+    match args.subcommand() {
+        Some(("banner", sub_args)) => return banner::run(sub_args),
+        Some(("batch", sub_args)) => return batch::run(sub_args),
+        Some(("history", sub_args)) => return history::run(sub_args),
+        Some(("lint", sub_args)) => return lint::run(sub_args),
+        Some(("paste", sub_args)) => return paste::run(sub_args),
+        Some(("roundtrip-check", sub_args)) => return roundtrip_check::run(sub_args),
+        Some(("status", sub_args)) => return status::run(sub_args),
+        Some(("styles", sub_args)) => return styles::run(sub_args),
+        Some(("table", sub_args)) => return table::run(sub_args),
+        #[cfg(feature = "bench")]
+        Some(("bench", sub_args)) => return bench::run(sub_args),
+        #[cfg(feature = "http")]
+        Some(("serve-http", sub_args)) => return serve_http::run(sub_args),
+        #[cfg(feature = "self-update")]
+        Some(("self-update", sub_args)) => return self_update::run(sub_args),
+        #[cfg(feature = "targets")]
+        Some(("targets", sub_args)) => return targets::run(sub_args),
+        #[cfg(feature = "tui")]
+        Some(("tui", sub_args)) => return tui::run(sub_args),
+        _ => (),
+    }
+
+    clipboard::check_args(&args)?;
+    if let Some(&text_len) = args.get_one::<usize>(clipboard::HOLDER_ARG) {
+        return clipboard::run_holder(&args, text_len);
+    }
+    if args.get_flag("kill") {
+        return holder::kill();
+    }
+
+    let config = Config::load(args.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let style = chain::find(&config, args.get_one::<String>("style").unwrap())?
+        .map(|style| cli::style_args(&args, style));
+    let protect = match args.get_one::<PathBuf>("protect-file") {
+        Some(path) => Protect::parse(
+            &fs::read_to_string(path)
+                .with_context(|| format!("failed to read '{}'", path.display()))?,
+        ),
+        None => Protect::new(config.protect.iter().map(String::as_str)),
+    };
+    if args.get_flag("interactive") {
+        return repl::run(&args, &style);
+    }
+
+    let keep_trailing_newline = args.get_flag("keep-trailing-newline");
+    let lossy = args.get_flag("lossy");
+    let line_endings = LineEndings::from_name(args.get_one::<String>("crlf").unwrap()).unwrap();
+
+    // only --benchmark looks at this, but timing is cheap next to converting
+    let bench = Benchmark::default();
+    let timed_convert = |input: String| {
+        let start = Instant::now();
+        let bytes = input.len();
+        let result = convert(&args, &style, &config, &protect, input);
+        bench.record(bytes, start.elapsed());
+        result
+    };
 
-    let mut text = String::new();
-    if args.contains_id("text") {
-        let mut words = args.get_many::<String>("text").unwrap().peekable();
-        while let Some(word) = words.next() {
-            text.extend(word.chars().map(fw_char));
-            if words.peek().is_some() {
-                text.push(WIDE_SPACE);
+    let edit = args.get_flag("edit");
+    if !args.contains_id("text") && !edit {
+        input::prompt_if_terminal();
+        if let Some(&secs) = args.get_one::<u64>("stdin-timeout") {
+            input::wait_for_stdin(Duration::from_secs(secs))?;
+        }
+    }
+
+    if !args.contains_id("text") && !edit && can_stream(&args, &config) {
+        let text = stream::run(
+            line_endings,
+            keep_trailing_newline,
+            lossy,
+            // a dry run still describes what it would copy
+            clipboard::enabled(&args) || clipboard::dry_run(&args),
+            jobs(&args),
+            |input| Ok(timed_convert(input)?.1),
+        )?;
+        if args.get_flag("benchmark") {
+            bench.report();
+        }
+        if let Some(text) = text {
+            clipboard::copy(&args, &lossy::replace(&text))?;
+        }
+        return Ok(());
+    }
+
+    if args.get_flag("each") {
+        let mut texts = Vec::new();
+        let mut stdout = io::stdout().lock();
+        let words = args.get_many::<String>("text").unwrap();
+        let copy_index = args.get_one::<CopyIndex>("copy-index").copied();
+        if let Some(CopyIndex::Nth(n)) = copy_index {
+            if n > words.len() {
+                bail!(
+                    "--copy-index {n} is past the last of the {} texts",
+                    words.len()
+                );
             }
         }
-    } else {
-        let mut input = String::new();
-        std::io::stdin()
-            .lock()
-            .read_to_string(&mut input)
-            .context("failed to read stdin")?;
-
-        if input.ends_with('\n') {
-            input.pop();
+        for word in words {
+            let (input, text) = timed_convert(word.clone())?;
+            let shown = match args.get_one::<String>("tee") {
+                Some(template) => Cow::Owned(tee(template, word, &text)),
+                None => Cow::Borrowed(&text),
+            };
+            write!(stdout, "{shown}{}", line_endings.terminator())
+                .context("failed to write output")?;
+            if args.get_flag("history") && !clipboard::dry_run(&args) {
+                history::append(&input, &text)?;
+            }
+            texts.push(text);
+        }
+        stdout.flush().context("failed to write output")?;
+        drop(stdout);
+        if args.get_flag("benchmark") {
+            bench.report();
         }
-        text.extend(input.chars().map(fw_char));
-    }
-    println!("{text}");
-
-    if !args.get_flag("no-clipboard") && env_is_nonempty("DISPLAY") {
-        let mode = if args.get_flag("no-wait") {
-            WaitMode::NoWait
-        } else if args.get_flag("foreground-wait") {
-            WaitMode::Foreground
-        } else if env_is_nonempty("XDG_CURRENT_DESKTOP") {
-            // In Gnome, it seems like we can get away with setting the clipboard then immediately
-            // exiting. I guess something else in the desktop session picks it up.
-            // TODO verify that this is the right env var to check
-            WaitMode::NoWait
+        let copied = match copy_index {
+            Some(CopyIndex::Nth(n)) => texts.swap_remove(n - 1),
+            Some(CopyIndex::Last) => texts.pop().unwrap_or_default(),
+            Some(CopyIndex::All) | None => texts.join(line_endings.terminator()),
+        };
+        return clipboard::copy(&args, &copied);
+    }
+
+    let input = match args.get_many::<String>("text") {
+        Some(words) => {
+            // spaces become wide spaces when converted
+            let mut input = String::with_capacity(words.clone().map(|w| w.len() + 1).sum());
+            for (i, word) in words.enumerate() {
+                if i > 0 {
+                    input.push(' ');
+                }
+                input.push_str(word);
+            }
+            if edit {
+                input::edit(&input, keep_trailing_newline)?
+            } else {
+                input
+            }
+        }
+        None if edit => input::edit("", keep_trailing_newline)?,
+        None if lossy => input::read_stdin_lossy(keep_trailing_newline)?,
+        None => match args.get_one::<&Encoding>("input-encoding") {
+            Some(encoding) => input::read_stdin_encoded(encoding, keep_trailing_newline)?,
+            None => input::read_stdin(keep_trailing_newline)?,
+        },
+    };
+    let orig = args
+        .get_one::<String>("tee")
+        .map(|template| (template, input.clone()));
+    let (input, text) = timed_convert(input)?;
+    if args.get_flag("benchmark") {
+        bench.report();
+    }
+    let terminator = if keep_trailing_newline {
+        ""
+    } else {
+        line_endings.terminator()
+    };
+    // scoped so that stdout is unlocked again before we copy
+    {
+        let mut stdout = io::stdout().lock();
+        let shown = match &orig {
+            Some((template, orig)) => Cow::Owned(tee(template, orig, &text)),
+            None => Cow::Borrowed(&text),
+        };
+        let bytes = if lossy {
+            lossy::encode(&shown)
         } else {
-            // by default if we don't think we're in a desktop session, fork to the background to
-            // wait and serve clipboard requests.
-            WaitMode::Background
+            shown.as_bytes().into()
         };
+        stdout
+            .write_all(&bytes)
+            .and_then(|()| stdout.write_all(terminator.as_bytes()))
+            .and_then(|()| stdout.flush())
+            .context("failed to write output")?;
+    }
 
-        set_clipboard(&text, mode)?;
+    // invalid bytes from --lossy can't go anywhere but stdout
+    let text = lossy::replace(&text);
+    if args.get_flag("history") && !clipboard::dry_run(&args) {
+        history::append(&lossy::replace(&input), &text)?;
     }
+    clipboard::copy(&args, &text)?;
 
     Ok(())
 }
 
+/// Which of the `--each` texts to copy, see `--copy-index`.
+#[derive(Debug, Clone, Copy)]
+enum CopyIndex {
+    /// Counting from 1.
+    Nth(usize),
+    Last,
+    All,
+}
+
+impl CopyIndex {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "last" => Ok(Self::Last),
+            "all" => Ok(Self::All),
+            _ => match s.parse() {
+                Ok(0) | Err(_) => Err("expected a number from 1, 'last', or 'all'".to_owned()),
+                Ok(n) => Ok(Self::Nth(n)),
+            },
+        }
+    }
+}
+
+/// Conversion timing for `--benchmark`. With `--jobs` the time is added up across threads, so
+/// it measures the conversion itself rather than how well it parallelizes.
+#[derive(Default)]
+struct Benchmark {
+    nanos: AtomicU64,
+    bytes: AtomicUsize,
+}
+
+impl Benchmark {
+    fn record(&self, bytes: usize, elapsed: Duration) {
+        self.nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn report(&self) {
+        let elapsed = Duration::from_nanos(self.nanos.load(Ordering::Relaxed));
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        eprintln!(
+            "converted {bytes} bytes in {elapsed:.3?} ({:.1} MiB/s)",
+            bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+        );
+    }
+}
+
+/// How many threads to convert with, see `--jobs`.
+fn jobs(args: &ArgMatches) -> usize {
+    match args.get_one::<usize>("jobs") {
+        Some(&jobs) => jobs.max(1),
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+}
+
+/// Whether the options in `args` can be applied to stdin a few lines at a time. Options that
+/// need to see the whole text at once, like `--box` measuring the widest line, can't stream.
+fn can_stream(args: &ArgMatches, config: &Config) -> bool {
+    // one report for the whole input rather than one per batch
+    if args.get_flag("strip-invisible") && args.get_flag("verbose") {
+        return false;
+    }
+    let affixes = affixes(args, config);
+    if !affixes.prefix.is_empty() || !affixes.suffix.is_empty() {
+        return false;
+    }
+    // a JSON string has to be quoted as a whole
+    if args.get_one::<String>("format").unwrap() == Format::JsonString.name() {
+        return false;
+    }
+    // placeholders can span lines
+    ![
+        "decorate",
+        "box",
+        "cjk-punct",
+        "history",
+        "template",
+        "repeat",
+        "input-encoding",
+        // the original is printed with the whole output, not with each batch
+        "tee",
+        // the quote marks are per line, but the numbers count across batches
+        "number",
+        "max-chars",
+        "max-width",
+        // the random choices carry on from one line to the next
+        "seed",
+        "chaos",
+    ]
+    .iter()
+    .any(|&id| {
+        !matches!(
+            args.value_source(id),
+            None | Some(ValueSource::DefaultValue)
+        )
+    })
+}
+
+/// Fill in a `--tee` template with the original and converted text, in one pass so that
+/// braces in the text are left alone.
+fn tee(template: &str, orig: &str, out: &str) -> String {
+    let mut shown = String::with_capacity(template.len() + orig.len() + out.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '\\']) {
+        shown.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let (insert, len) = [
+            ("{orig}", orig),
+            ("{out}", out),
+            ("\\n", "\n"),
+            ("\\\\", "\\"),
+        ]
+        .into_iter()
+        .find(|(key, _)| rest.starts_with(key))
+        .map_or((&rest[..1], 1), |(key, insert)| (insert, key.len()));
+        shown.push_str(insert);
+        rest = &rest[len..];
+    }
+    shown.push_str(rest);
+    shown
+}
+
+/// The text to add around the output from `--prefix` and `--suffix`, or `--wrap-with`, falling
+/// back to the config file's.
+fn affixes(args: &ArgMatches, config: &Config) -> Decoration {
+    let wrap_with = args.get_one::<String>("wrap-with");
+    let pick = |id, default: &String| args.get_one(id).or(wrap_with).unwrap_or(default).clone();
+    Decoration {
+        prefix: pick("prefix", &config.prefix),
+        suffix: pick("suffix", &config.suffix),
+    }
+}
+
+/// The library's conversion options for `args`, converting to `style` and leaving the
+/// `protect`ed words alone.
+fn convert_options(args: &ArgMatches, style: &Chain, protect: &Protect) -> Options {
+    let chaos = (args.value_source("chaos") == Some(ValueSource::CommandLine)).then(|| {
+        let styles = args.get_one::<Vec<Style>>("chaos-styles").unwrap();
+        let styles = styles.iter().map(|&style| cli::style_args(args, style));
+        let seed = match args.get_one::<u64>("chaos") {
+            Some(&seed) => seed,
+            None => cli::seed_or_clock(args),
+        };
+        (styles.collect(), seed)
+    });
+    let classes = match (
+        args.get_one::<Classes>("only"),
+        args.get_one::<Classes>("except"),
+    ) {
+        (Some(&only), _) => only,
+        (_, Some(&except)) => except.complement(),
+        _ => Classes::ALL,
+    };
+    let expand_tabs =
+        args.contains_id("expand-tabs")
+            .then(|| match args.get_one::<usize>("expand-tabs") {
+                Some(&n) => Tabs::Fixed(n),
+                None => Tabs::Stops(8),
+            });
+    let pad = args.get_one::<usize>("pad").map(|&cols| {
+        let align = Align::from_name(args.get_one::<String>("align").unwrap()).unwrap();
+        (cols, align)
+    });
+    let limit = match (args.get_one("max-chars"), args.get_one("max-width")) {
+        (Some(&n), _) => Some(Limit::Chars(n)),
+        (_, Some(&cols)) => Some(Limit::Width(cols)),
+        _ => None,
+    };
+    let truncate = limit.map(|limit| {
+        let mode = Truncate::from_name(args.get_one::<String>("truncate-mode").unwrap()).unwrap();
+        let ellipsis = args.get_one::<String>("ellipsis").unwrap().clone();
+        (limit, mode, ellipsis)
+    });
+    Options {
+        style: style.clone(),
+        chaos,
+        classes,
+        decode: args.get_flag("decode"),
+        decode_first: args.get_flag("decode-first"),
+        skip_converted: args.get_flag("skip-converted"),
+        emoji: args.get_flag("emoji"),
+        case: args
+            .get_one::<String>("case")
+            .map(|name| Case::from_name(name, cli::seed_or_clock(args)).unwrap()),
+        cjk_punct: args.get_flag("cjk-punct"),
+        leet: args
+            .get_flag("leet")
+            .then(|| *args.get_one::<u8>("leet-level").unwrap()),
+        digits: args
+            .get_one::<String>("digits")
+            .map(|name| Digits::from_name(name).unwrap()),
+        strip_ansi: args.get_flag("strip-ansi"),
+        strip_invisible: args.get_flag("strip-invisible"),
+        keep_urls: args.get_flag("keep-urls"),
+        protect: protect.clone(),
+        template: args.get_flag("template").then(|| args.get_flag("invert")),
+        bidi_isolate: args.get_one::<String>("bidi").unwrap() == "isolate",
+        expand_tabs,
+        wrap: args.get_one::<usize>("wrap").copied(),
+        number: args.get_flag("number"),
+        quote: args.get_flag("quote"),
+        pad,
+        draw_box: args.get_one::<BoxChars>("box").copied(),
+        truncate,
+        format: Format::from_name(args.get_one::<String>("format").unwrap()).unwrap(),
+    }
+}
+
+/// Convert `input` according to `args`. Returns the input after preprocessing like `--emoji`,
+/// which is what the history records, along with the converted text. With `--template` or
+/// `--keep-urls` or protected words the input is only preprocessed piece by piece, so it's
+/// returned as it was.
+fn convert(
+    args: &ArgMatches,
+    style: &Chain,
+    config: &Config,
+    protect: &Protect,
+    input: String,
+) -> anyhow::Result<(String, String)> {
+    let line_endings = LineEndings::from_name(args.get_one::<String>("crlf").unwrap()).unwrap();
+    let input = line_endings.apply(input);
+    let options = convert_options(args, style, protect);
+    let Converted {
+        input,
+        mut text,
+        removed,
+    } = convert::convert_text(&input, &options);
+    if args.get_flag("verbose") {
+        for (c, name, n) in removed.iter() {
+            tracing::info!("removed {n} U+{:04X} {name}", c as u32);
+        }
+    }
+    if args.get_one::<String>("bidi").unwrap() == "warn" && bidi::has_rtl(&text) {
+        // once, rather than for every batch of lines when streaming
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| {
+            tracing::warn!(
+                "the text has right-to-left parts, which can show up out of order once \
+                 converted (--bidi isolate keeps them together)"
+            )
+        });
+    }
+    if let Some(name) = args.get_one::<String>("decorate") {
+        text = decorate::find(config, name)?.apply(&text);
+    }
+    text = affixes(args, config).apply(&text);
+    if let Some(&n) = args.get_one::<u32>("repeat") {
+        let separator = args
+            .get_one::<String>("repeat-separator")
+            .map_or(line_endings.terminator(), String::as_str);
+        text = vec![text.as_str(); n as usize].join(separator);
+    }
+    Ok((input, convert::lay_out(text, &options)))
+}
+
 fn main() {
     if let Err(err) = run() {
-        eprintln!("Error: {err:#}");
+        log::init_default();
+        tracing::error!("{err:#}");
         std::process::exit(1);
     }
 }
@@ -0,0 +1,58 @@
+//! Handing text directly to a clipboard manager.
+//!
+//! When a manager with an IPC interface is running, asking it to set the clipboard means the
+//! contents are owned by a long-lived process from the start, so we don't need to start a holder
+//! and wait around to serve paste requests ourselves.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context};
+
+use crate::detect::Manager;
+
+/// Set the clipboard contents through `manager`'s own API.
+pub fn handoff(manager: Manager, text: &str) -> anyhow::Result<()> {
+    match manager {
+        Manager::Klipper => {
+            let status = Command::new("dbus-send")
+                .args([
+                    "--session",
+                    "--type=method_call",
+                    "--dest=org.kde.klipper",
+                    "/klipper",
+                    "org.kde.klipper.klipper.setClipboardContents",
+                ])
+                .arg(format!("string:{text}"))
+                .stdin(Stdio::null())
+                .status()
+                .context("failed to run dbus-send")?;
+            if !status.success() {
+                bail!("dbus-send to klipper failed ({status})");
+            }
+            Ok(())
+        }
+        Manager::CopyQ => {
+            // pass the text on stdin to avoid argument length limits and copyq's own parsing of
+            // arguments that look like options
+            let mut child = Command::new("copyq")
+                .args(["copy", "-"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+                .context("failed to run copyq")?;
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(text.as_bytes())
+                .context("failed to write to copyq")?;
+            let status = child.wait().context("failed to wait for copyq")?;
+            if !status.success() {
+                bail!("copyq copy failed ({status})");
+            }
+            Ok(())
+        }
+        other => bail!("clipboard manager {other:?} doesn't support direct handoff"),
+    }
+}
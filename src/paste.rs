@@ -0,0 +1,85 @@
+//! `fw paste`, printing the clipboard contents as they are.
+
+use std::io::{self, Write};
+use std::process::Command as Process;
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::env_is_nonempty;
+use crate::selection::Reader;
+
+pub fn command() -> Command {
+    Command::new("paste")
+        .about("Print what's on the clipboard, without converting it")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["text", "html", "image"])
+                .default_value("text")
+                .help("Which form of the contents to print. Images are printed as PNG"),
+        )
+        .arg(
+            Arg::new("primary")
+                .long("primary")
+                .action(ArgAction::SetTrue)
+                .help("Print the primary selection instead of the clipboard"),
+        )
+}
+
+/// The targets to ask for each `--format`, best first.
+fn targets(format: &str) -> &'static [&'static str] {
+    match format {
+        "html" => &["text/html"],
+        "image" => &["image/png"],
+        _ => &["UTF8_STRING", "text/plain;charset=utf-8"],
+    }
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let format = args.get_one::<String>("format").unwrap();
+    let primary = args.get_flag("primary");
+    let data = if env_is_nonempty("WAYLAND_DISPLAY") {
+        wayland_paste(format, primary)?
+    } else if env_is_nonempty("DISPLAY") {
+        x11_paste(format, primary)?
+    } else {
+        bail!("no X11 or Wayland display found");
+    };
+    let mut stdout = io::stdout().lock();
+    stdout
+        .write_all(&data)
+        .and_then(|()| stdout.flush())
+        .context("failed to write output")
+}
+
+fn wayland_paste(format: &str, primary: bool) -> anyhow::Result<Vec<u8>> {
+    let mut cmd = Process::new("wl-paste");
+    cmd.args(["--no-newline", "--type", targets(format)[0]]);
+    if primary {
+        cmd.arg("--primary");
+    }
+    let output = cmd.output().context("failed to run wl-paste")?;
+    if !output.status.success() {
+        bail!(
+            "wl-paste failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn x11_paste(format: &str, primary: bool) -> anyhow::Result<Vec<u8>> {
+    let reader = Reader::connect()?;
+    let selection = reader.selection(primary)?;
+    if reader.owner(selection)?.is_none() {
+        bail!("the selection is empty");
+    }
+    for target in targets(format) {
+        if let Some(data) = reader.convert(selection, reader.intern(target)?)? {
+            return Ok(data.bytes);
+        }
+    }
+    bail!("the selection has no {format}");
+}
@@ -0,0 +1,32 @@
+//! XDG base directory lookups.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+/// Look up an XDG base directory from `var`, falling back to `$HOME/<fallback>` if it's unset or
+/// not an absolute path (as the spec requires).
+fn xdg_dir(var: &str, fallback: &str) -> anyhow::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(var).map(PathBuf::from) {
+        if dir.is_absolute() {
+            return Ok(dir);
+        }
+    }
+    let home = std::env::var_os("HOME").context("HOME is not set")?;
+    Ok(PathBuf::from(home).join(fallback))
+}
+
+/// fw's data directory, `$XDG_DATA_HOME/fw`.
+pub fn data_dir() -> anyhow::Result<PathBuf> {
+    Ok(xdg_dir("XDG_DATA_HOME", ".local/share")?.join("fw"))
+}
+
+/// fw's config directory, `$XDG_CONFIG_HOME/fw`.
+pub fn config_dir() -> anyhow::Result<PathBuf> {
+    Ok(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("fw"))
+}
+
+/// fw's state directory, `$XDG_STATE_HOME/fw`.
+pub fn state_dir() -> anyhow::Result<PathBuf> {
+    Ok(xdg_dir("XDG_STATE_HOME", ".local/state")?.join("fw"))
+}
@@ -0,0 +1,276 @@
+//! A small built-in fuzzy picker, so that `fw history --pick` doesn't need fzf.
+//!
+//! It draws on the alternate screen of the controlling terminal (not stdout, which gets the
+//! chosen entry) with plain escape sequences, reading keys in raw mode.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+
+use anyhow::Context;
+use fw::layout;
+
+/// Something to pick.
+pub struct Item {
+    /// The line shown in the list, which is also what the query matches against.
+    pub label: String,
+    /// Shown below the list for the selected item.
+    pub preview: String,
+}
+
+/// Score how well `query` matches `text` as a case-insensitive subsequence, or None if it
+/// doesn't. Higher is better: consecutive matches and matches at the start of words count for
+/// more, and gaps cost a little.
+fn score(query: &[char], text: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let mut score = 0;
+    let mut q = 0;
+    let mut prev_match: Option<usize> = None;
+    let mut prev_char = ' ';
+    for (i, c) in text.chars().enumerate() {
+        if q < query.len() && c.to_lowercase().eq(query[q].to_lowercase()) {
+            score += 10;
+            match prev_match {
+                Some(p) if p + 1 == i => score += 15,
+                Some(p) => score -= (i - p).min(10) as i64,
+                None => (),
+            }
+            if !prev_char.is_alphanumeric() {
+                score += 10;
+            }
+            prev_match = Some(i);
+            q += 1;
+        }
+        prev_char = c;
+    }
+    (q == query.len()).then_some(score)
+}
+
+/// The terminal in raw mode, put back the way it was on drop.
+struct Terminal {
+    tty: File,
+    saved: libc::termios,
+}
+
+impl Terminal {
+    fn open() -> anyhow::Result<Self> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .context("the picker needs a terminal")?;
+        let fd = tty.as_raw_fd();
+        // SAFETY: termios is plain data, and fd is an open terminal
+        let saved = unsafe {
+            let mut saved = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut saved) < 0 {
+                return Err(io::Error::last_os_error()).context("failed to get terminal mode");
+            }
+            let mut raw = saved;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(fd, libc::TCSAFLUSH, &raw) < 0 {
+                return Err(io::Error::last_os_error()).context("failed to set terminal mode");
+            }
+            saved
+        };
+        let mut term = Self { tty, saved };
+        // alternate screen, hide the cursor
+        term.write(b"\x1b[?1049h\x1b[?25l")?;
+        Ok(term)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.tty
+            .write_all(bytes)
+            .and_then(|()| self.tty.flush())
+            .context("failed to write to the terminal")
+    }
+
+    /// The terminal's size in columns and rows.
+    fn size(&self) -> (usize, usize) {
+        // SAFETY: winsize is plain data filled in by the ioctl
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(self.tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } < 0
+            || size.ws_col == 0
+        {
+            return (80, 24);
+        }
+        (size.ws_col.into(), size.ws_row.into())
+    }
+
+    /// Whether there's more input within a moment, to tell a lone Esc from an escape sequence.
+    fn input_pending(&self) -> bool {
+        let mut fd = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: a single valid pollfd
+        unsafe { libc::poll(&mut fd, 1, 50) > 0 }
+    }
+
+    fn read_byte(&mut self) -> anyhow::Result<u8> {
+        let mut byte = [0];
+        loop {
+            match self.tty.read(&mut byte) {
+                Ok(0) => anyhow::bail!("the terminal went away"),
+                Ok(_) => return Ok(byte[0]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err).context("failed to read from the terminal"),
+            }
+        }
+    }
+
+    fn read_key(&mut self) -> anyhow::Result<Key> {
+        let byte = self.read_byte()?;
+        Ok(match byte {
+            b'\r' | b'\n' => Key::Enter,
+            0x03 | 0x07 => Key::Cancel,
+            0x7f | 0x08 => Key::Backspace,
+            0x15 => Key::Clear,
+            0x10 | 0x0b => Key::Up,
+            0x0e => Key::Down,
+            0x1b if !self.input_pending() => Key::Cancel,
+            0x1b => {
+                let kind = self.read_byte()?;
+                let code = self.read_byte()?;
+                match (kind, code) {
+                    (b'[' | b'O', b'A') => Key::Up,
+                    (b'[' | b'O', b'B') => Key::Down,
+                    _ => Key::Other,
+                }
+            }
+            0x00..=0x1f => Key::Other,
+            _ => {
+                // the rest of a UTF-8 sequence, going by how many leading bits are set
+                let len = match byte.leading_ones() {
+                    2 => 2,
+                    3 => 3,
+                    4 => 4,
+                    _ => 1,
+                };
+                let mut buf = vec![byte];
+                for _ in 1..len {
+                    buf.push(self.read_byte()?);
+                }
+                match std::str::from_utf8(&buf)
+                    .ok()
+                    .and_then(|s| s.chars().next())
+                {
+                    Some(c) => Key::Char(c),
+                    None => Key::Other,
+                }
+            }
+        })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.write(b"\x1b[?25h\x1b[?1049l");
+        // SAFETY: putting back the mode we saved
+        unsafe { libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSAFLUSH, &self.saved) };
+    }
+}
+
+enum Key {
+    Char(char),
+    Backspace,
+    Clear,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+    Other,
+}
+
+/// Push `line` cut down to `cols` columns, and move to the next line.
+fn push_line(screen: &mut String, line: &str, cols: usize) {
+    // tabs and newlines in a label would throw the layout off
+    let line: String = line
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    screen.push_str(layout::truncate_line(&line, cols).0);
+    screen.push_str("\x1b[K\r\n");
+}
+
+/// Let the user fuzzy-search `items` and return the index of the chosen one, or None if they
+/// cancelled.
+pub fn pick(items: &[Item]) -> anyhow::Result<Option<usize>> {
+    let mut term = Terminal::open()?;
+    let mut query = String::new();
+    // indices into items, best match first
+    let mut matches: Vec<usize> = (0..items.len()).collect();
+    let mut selected = 0;
+    let mut scroll = 0;
+
+    loop {
+        let (cols, rows) = term.size();
+        // the query line, then the list, a separator, and the preview in the bottom half
+        let preview_rows = rows / 2;
+        let list_rows = rows.saturating_sub(preview_rows + 2).max(1);
+        selected = selected.min(matches.len().saturating_sub(1));
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + list_rows {
+            scroll = selected + 1 - list_rows;
+        }
+
+        let mut screen = String::from("\x1b[H");
+        push_line(
+            &mut screen,
+            &format!("> {query}  ({}/{})", matches.len(), items.len()),
+            cols,
+        );
+        for row in 0..list_rows {
+            match matches.get(scroll + row) {
+                Some(&i) if scroll + row == selected => {
+                    screen.push_str("\x1b[7m");
+                    push_line(&mut screen, &items[i].label, cols);
+                    screen.push_str("\x1b[0m");
+                }
+                Some(&i) => push_line(&mut screen, &items[i].label, cols),
+                None => screen.push_str("\x1b[K\r\n"),
+            }
+        }
+        push_line(&mut screen, &"─".repeat(cols), cols);
+        let preview = matches
+            .get(selected)
+            .map_or("", |&i| items[i].preview.as_str());
+        let mut lines = preview.lines();
+        for _ in 0..preview_rows {
+            push_line(&mut screen, lines.next().unwrap_or_default(), cols);
+        }
+        term.write(screen.trim_end_matches("\r\n").as_bytes())?;
+
+        let old_query = query.clone();
+        match term.read_key()? {
+            Key::Char(c) => query.push(c),
+            Key::Backspace => {
+                query.pop();
+            }
+            Key::Clear => query.clear(),
+            Key::Up => selected = selected.saturating_sub(1),
+            Key::Down => selected += 1,
+            Key::Enter => return Ok(matches.get(selected).copied()),
+            Key::Cancel => return Ok(None),
+            Key::Other => (),
+        }
+        if query != old_query {
+            let chars: Vec<char> = query.chars().collect();
+            let mut scored: Vec<(i64, usize)> = items
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| score(&chars, &item.label).map(|s| (s, i)))
+                .collect();
+            // stable, so equally good matches stay in history order
+            scored.sort_by_key(|&(s, _)| std::cmp::Reverse(s));
+            matches = scored.into_iter().map(|(_, i)| i).collect();
+            selected = 0;
+            scroll = 0;
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Bundles of options from the config file for `--profile`.
+//!
+//! ```toml
+//! [profiles.chat]
+//! style = "fullwidth"
+//! skip-converted = true
+//! clear-after = "2m"
+//!
+//! [profiles.discord]
+//! inherits = "chat"
+//! max-chars = 2000
+//! ```
+//!
+//! Each key is the long name of an option, set as if it came before everything on the command
+//! line, so options that are given there too win. A profile's own options replace the ones it
+//! inherits, and setting a flag to false turns off an inherited one.
+
+use std::ffi::OsString;
+
+use anyhow::bail;
+use clap::{ArgMatches, Command};
+use serde::Deserialize;
+use toml::Value;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Another profile to start from.
+    pub inherits: Option<String>,
+    /// Everything else, by option name.
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+/// Options that choose where the profile comes from, which a profile can't sensibly set.
+const RESERVED: [&str; 2] = ["profile", "config"];
+
+/// Turn the profile called `name` into command line arguments for `cmd`, inherited ones first.
+fn args(cmd: &Command, config: &Config, name: &str) -> anyhow::Result<Vec<OsString>> {
+    let mut chain: Vec<(&str, &Profile)> = Vec::new();
+    let mut next = Some(name);
+    while let Some(name) = next {
+        if chain.iter().any(|&(n, _)| n == name) {
+            let path: Vec<&str> = chain.iter().map(|&(n, _)| n).collect();
+            bail!(
+                "profile '{name}' inherits from itself: {} -> {name}",
+                path.join(" -> ")
+            );
+        }
+        let Some(profile) = config.profiles.get(name) else {
+            match chain.last() {
+                Some((child, _)) => bail!("profile '{child}' inherits unknown profile '{name}'"),
+                None => {
+                    let mut names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                    names.sort_unstable();
+                    bail!("unknown profile '{name}' (available: {})", names.join(", "));
+                }
+            }
+        };
+        chain.push((name, profile));
+        next = profile.inherits.as_deref();
+    }
+
+    // a profile's own options replace the inherited ones, so `false` can turn a flag back off
+    let mut options = toml::Table::new();
+    for (name, profile) in chain.into_iter().rev() {
+        for (key, value) in &profile.options {
+            let known = cmd.get_arguments().any(|arg| arg.get_long() == Some(key));
+            if !known || RESERVED.contains(&key.as_str()) {
+                bail!("profile '{name}' sets '{key}', which isn't an option it can set");
+            }
+            let values = match value {
+                Value::Array(values) => values.as_slice(),
+                value => std::slice::from_ref(value),
+            };
+            if !values
+                .iter()
+                .all(|v| v.is_str() || v.is_integer() || v.is_float() || v.is_bool())
+            {
+                bail!("profile '{name}' sets '{key}' to a table or a date, not an option value");
+            }
+            options.insert(key.clone(), value.clone());
+        }
+    }
+
+    let mut args = Vec::new();
+    for (key, value) in &options {
+        let values = match value {
+            Value::Array(values) => values.as_slice(),
+            value => std::slice::from_ref(value),
+        };
+        for value in values {
+            match value {
+                Value::Boolean(true) => args.push(format!("--{key}").into()),
+                Value::Boolean(false) => (),
+                Value::String(s) => args.push(format!("--{key}={s}").into()),
+                value => args.push(format!("--{key}={value}").into()),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Parse the command line again with the profile called `name` in front of it.
+pub fn matches(cmd: Command, config: &Config, name: &str) -> anyhow::Result<ArgMatches> {
+    let mut argv = std::env::args_os();
+    let mut full: Vec<OsString> = argv.next().into_iter().collect();
+    full.extend(args(&cmd, config, name)?);
+    full.extend(argv);
+    Ok(cmd.get_matches_from(full))
+}
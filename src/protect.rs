@@ -0,0 +1,152 @@
+//! Words and phrases that are never converted, like usernames and product names, so that
+//! `--protect-file` can keep them readable and searchable.
+//!
+//! Matching ignores case. A plain word or phrase matches wherever it isn't part of a longer
+//! word, and a pattern with `*` or `?` in it matches whole whitespace-separated words, less any
+//! brackets, quotes, and sentence punctuation around them.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pattern {
+    Phrase(Vec<char>),
+    Glob(Vec<char>),
+}
+
+/// A list of protected words and patterns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Protect {
+    patterns: Vec<Pattern>,
+}
+
+fn chars_eq(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Whether `word` matches `glob`, where `*` is any run of chars and `?` is any one char.
+fn glob_match(glob: &[char], word: &[char]) -> bool {
+    match glob.split_first() {
+        None => word.is_empty(),
+        Some(('*', rest)) => (0..=word.len()).any(|i| glob_match(rest, &word[i..])),
+        Some((&g, rest)) => word
+            .split_first()
+            .is_some_and(|(&c, word)| (g == '?' || chars_eq(g, c)) && glob_match(rest, word)),
+    }
+}
+
+/// The length in bytes of `phrase` at the start of `text`, if it's there and doesn't run into
+/// a longer word.
+fn phrase_at(phrase: &[char], text: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+    for &p in phrase {
+        match chars.next() {
+            Some((_, c)) if chars_eq(p, c) => (),
+            _ => return None,
+        }
+    }
+    let end = chars.next().map_or(text.len(), |(i, _)| i);
+    let runs_on = text[end..].starts_with(char::is_alphanumeric)
+        && phrase.last().is_some_and(|c| c.is_alphanumeric());
+    (!runs_on).then_some(end)
+}
+
+impl Protect {
+    /// Parse a list with one word, phrase, or pattern per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn parse(list: &str) -> Self {
+        Self::new(list.lines())
+    }
+
+    /// Protect each of `patterns`, ignoring blank ones and `#` comments like [`Protect::parse`].
+    pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .map(str::trim)
+            .filter(|p| !p.is_empty() && !p.starts_with('#'))
+            .map(|p| {
+                if p.contains(['*', '?']) {
+                    Pattern::Glob(p.chars().collect())
+                } else {
+                    Pattern::Phrase(p.chars().collect())
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Byte ranges of every protected part of `text`, in order and without overlaps.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        let mut offset = 0;
+        for word in text.split_inclusive(char::is_whitespace) {
+            let trimmed = word.trim_end_matches(char::is_whitespace);
+            let start =
+                trimmed.len() - trimmed.trim_start_matches(['(', '<', '[', '"', '\'']).len();
+            let glob_word = &trimmed[start..];
+            let glob_word = glob_word
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'', '>', ']', ')']);
+            let chars: Vec<char> = glob_word.chars().collect();
+            if self.patterns.iter().any(|p| match p {
+                Pattern::Glob(glob) => !chars.is_empty() && glob_match(glob, &chars),
+                Pattern::Phrase(_) => false,
+            }) {
+                found.push(offset + start..offset + start + glob_word.len());
+            }
+            offset += word.len();
+        }
+
+        let mut prev = None;
+        for (i, c) in text.char_indices() {
+            let at_start = !prev.is_some_and(char::is_alphanumeric) || !c.is_alphanumeric();
+            prev = Some(c);
+            if !at_start {
+                continue;
+            }
+            let longest = self
+                .patterns
+                .iter()
+                .filter_map(|p| match p {
+                    Pattern::Phrase(phrase) => phrase_at(phrase, &text[i..]),
+                    Pattern::Glob(_) => None,
+                })
+                .max();
+            if let Some(len) = longest {
+                found.push(i..i + len);
+            }
+        }
+
+        // the earliest and then longest match wins where they overlap
+        found.sort_by_key(|range| (range.start, usize::MAX - range.end));
+        let mut ranges: Vec<Range<usize>> = Vec::with_capacity(found.len());
+        for range in found {
+            if ranges.last().is_none_or(|last| range.start >= last.end) {
+                ranges.push(range);
+            }
+        }
+        ranges
+    }
+
+    /// Convert everything in `text` except the protected parts with `convert`.
+    pub fn apply(&self, text: &str, mut convert: impl FnMut(&str) -> String) -> String {
+        if self.is_empty() {
+            return convert(text);
+        }
+        let mut out = String::with_capacity(text.len() * 3);
+        let mut last = 0;
+        for range in self.find(text) {
+            if range.start > last {
+                out.push_str(&convert(&text[last..range.start]));
+            }
+            out.push_str(&text[range.clone()]);
+            last = range.end;
+        }
+        if last < text.len() {
+            out.push_str(&convert(&text[last..]));
+        }
+        out
+    }
+}
@@ -0,0 +1,64 @@
+//! `fw -i`, converting and copying one line at a time.
+
+use anyhow::Context;
+use arboard::Clipboard;
+use clap::ArgMatches;
+use fw::style::Chain;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::{clipboard, history, paths};
+
+pub fn run(args: &ArgMatches, style: &Chain) -> anyhow::Result<()> {
+    let mut editor = DefaultEditor::new().context("failed to init line editor")?;
+    let history_path = paths::data_dir()?.join("repl_history");
+    // a missing history file is normal the first time around
+    let _ = editor.load_history(&history_path);
+
+    // One clipboard connection for the whole session. It serves paste requests while we wait for
+    // the next line, so there's no need to start a holder per snippet.
+    let mut cb = if clipboard::enabled(args) {
+        Some(Clipboard::new().context("failed to init clipboard")?)
+    } else {
+        None
+    };
+
+    let mut last = None;
+    loop {
+        let line = match editor.readline("fw> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => break,
+            Err(err) => return Err(err).context("failed to read line"),
+        };
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line.as_str());
+
+        let text = style.apply(&line);
+        println!("{text}");
+        if args.get_flag("history") && !clipboard::dry_run(args) {
+            history::append(&line, &text)?;
+        }
+        if let Some(cb) = cb.as_mut() {
+            cb.set_text(text.as_str())
+                .context("failed to set clipboard contents")?;
+        }
+        last = Some(text);
+    }
+
+    if let Some(dir) = history_path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Err(err) = editor.save_history(&history_path) {
+        tracing::warn!("failed to save line history: {err}");
+    }
+
+    // Dropping the last clipboard handle shuts down arboard's server thread before clipboard::copy
+    // starts a holder to keep the final snippet alive.
+    drop(cb);
+    if let Some(text) = last {
+        clipboard::copy(args, &text)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+//! Retrying flaky operations with exponential backoff.
+
+use std::thread;
+use std::time::Duration;
+
+/// Call `f` up to `attempts` times until it succeeds, sleeping `delay` after the first failure
+/// and twice as long after each one after that. Returns the last error if every attempt fails.
+pub fn retry<T>(
+    attempts: u32,
+    mut delay: Duration,
+    mut f: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => {
+                return Err(err.context(format!("gave up after {attempts} attempts")));
+            }
+            Err(_) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! The invariant tying [`Style::apply`] and [`Style::decode`] together: decoding converted text
+//! gives back the original, up to [`normalize`].
+
+use std::fmt;
+
+use crate::style::Style;
+
+/// What decoding `style.apply(text)` should return. Text that was already in the style before
+/// converting can't be told apart from converted text, so it decodes too. The round trip is only
+/// guaranteed for ASCII input, where this is `text` itself apart from odd control characters,
+/// and for leet and morse, uppercase letters. Leet also loses digits.
+pub fn normalize(style: Style, text: &str) -> String {
+    match style {
+        Style::Fullwidth
+        | Style::Circled
+        | Style::MathBold
+        | Style::Homoglyph { .. }
+        | Style::Braille => style.decode(text),
+        // leet has no case, so uppercase letters decode to lowercase
+        Style::Leet { .. } => {
+            let folded: String = text
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_uppercase() && style.apply(&c.to_string()) != c.to_string() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+            style.decode(&folded)
+        }
+        // morse has no case either
+        Style::Morse(_) => text.to_ascii_lowercase(),
+        Style::Spaced => text.to_owned(),
+        Style::Reversed => {
+            // a line starting with a lone \r reverses to one that looks like it ends in CRLF, so
+            // the \r comes back at the end
+            let mut out = String::with_capacity(text.len());
+            for line in text.split_inclusive('\n') {
+                match line.strip_prefix('\r').and_then(|l| l.strip_suffix('\n')) {
+                    Some(body) if !body.ends_with('\r') => {
+                        out.push_str(body);
+                        out.push_str("\r\n");
+                    }
+                    _ => out.push_str(line),
+                }
+            }
+            out
+        }
+    }
+}
+
+/// A failed round trip, returned by [`check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub style: Style,
+    pub expected: String,
+    pub decoded: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {:?} but decoded {:?}",
+            self.style.name(),
+            self.expected,
+            self.decoded
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Check that `text` survives converting to `style` and decoding again.
+pub fn check(style: Style, text: &str) -> Result<(), Mismatch> {
+    let decoded = style.decode(&style.apply(text));
+    let expected = normalize(style, text);
+    if decoded == expected {
+        Ok(())
+    } else {
+        Err(Mismatch {
+            style,
+            expected,
+            decoded,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+//! `fw roundtrip-check`, a hidden command for checking that `--decode` undoes every style.
+
+use anyhow::bail;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use fw::roundtrip;
+use fw::style::Style;
+
+use crate::{cli, input};
+
+pub fn command() -> Command {
+    Command::new("roundtrip-check")
+        .hide(true)
+        .about("Check that decoding converted text gives back the original")
+        .long_about(
+            "Check that decoding converted text gives back the original. Each argument, or \
+             each line of stdin if there are none, is converted and decoded with every style. \
+             Mismatches are printed and make the command fail.",
+        )
+        .arg(
+            cli::style_arg()
+                .default_value(None::<&str>)
+                .env(None::<&str>)
+                .help("Only check this style instead of all of them"),
+        )
+        .arg(
+            Arg::new("text")
+                .action(ArgAction::Append)
+                .help("Strings to check. Omit to check each line of stdin instead."),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let cases: Vec<String> = match args.get_many::<String>("text") {
+        Some(texts) => texts.cloned().collect(),
+        None => input::read_stdin(false)?
+            .split('\n')
+            .map(str::to_owned)
+            .collect(),
+    };
+    let styles = match args.get_one::<String>("style") {
+        Some(name) => vec![Style::from_name(name).unwrap()],
+        None => Style::ALL.to_vec(),
+    };
+
+    let mut failures = 0;
+    for case in &cases {
+        for &style in &styles {
+            if let Err(mismatch) = roundtrip::check(style, case) {
+                eprintln!("{mismatch}");
+                failures += 1;
+            }
+        }
+    }
+    if failures > 0 {
+        bail!("{failures} round trips failed");
+    }
+    println!("{} round trips ok", cases.len() * styles.len());
+    Ok(())
+}
@@ -0,0 +1,257 @@
+//! Reading the X11 selections directly.
+//!
+//! This is what [`holder`](crate::holder) is to setting the clipboard: a plain x11rb connection,
+//! without arboard's helper thread, that can ask for any target.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ConnectionExt, CreateWindowAux, EventMask, Property, Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE};
+
+/// How long to wait for the selection owner to answer.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A converted selection.
+pub struct Data {
+    /// The property type the owner answered with.
+    pub kind: Atom,
+    pub format: u8,
+    pub bytes: Vec<u8>,
+}
+
+impl Data {
+    /// The data as a list of 32-bit values, such as the atoms in a TARGETS reply.
+    #[cfg(feature = "targets")]
+    pub fn values32(&self) -> Option<Vec<u32>> {
+        if self.format != 32 {
+            return None;
+        }
+        Some(
+            self.bytes
+                .chunks_exact(4)
+                .map(|c| u32::from_ne_bytes(c.try_into().unwrap()))
+                .collect(),
+        )
+    }
+}
+
+pub struct Reader {
+    conn: RustConnection,
+    /// Selection conversions are delivered to a window, so we need one of our own.
+    window: Window,
+    property: Atom,
+    incr: Atom,
+}
+
+impl Reader {
+    pub fn connect() -> anyhow::Result<Self> {
+        let (conn, screen_num) =
+            x11rb::connect(None).context("failed to connect to the X server")?;
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id().context("failed to create a window")?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            // INCR transfers come as a series of property changes
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )
+        .context("failed to create a window")?;
+
+        let mut reader = Self {
+            conn,
+            window,
+            property: NONE,
+            incr: NONE,
+        };
+        reader.property = reader.intern("FW_SELECTION")?;
+        reader.incr = reader.intern("INCR")?;
+        Ok(reader)
+    }
+
+    pub fn intern(&self, name: &str) -> anyhow::Result<Atom> {
+        Ok(self
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .context("failed to intern atom")?
+            .reply()
+            .context("failed to intern atom")?
+            .atom)
+    }
+
+    #[cfg(feature = "targets")]
+    pub fn atom_name(&self, atom: Atom) -> anyhow::Result<String> {
+        let name = self
+            .conn
+            .get_atom_name(atom)
+            .context("failed to get atom name")?
+            .reply()
+            .context("failed to get atom name")?
+            .name;
+        Ok(String::from_utf8_lossy(&name).into_owned())
+    }
+
+    /// The CLIPBOARD selection, or PRIMARY if `primary` is set.
+    pub fn selection(&self, primary: bool) -> anyhow::Result<Atom> {
+        if primary {
+            Ok(AtomEnum::PRIMARY.into())
+        } else {
+            self.intern("CLIPBOARD")
+        }
+    }
+
+    /// The window owning `selection`, if any.
+    pub fn owner(&self, selection: Atom) -> anyhow::Result<Option<Window>> {
+        let owner = self
+            .conn
+            .get_selection_owner(selection)
+            .context("failed to get the selection owner")?
+            .reply()
+            .context("failed to get the selection owner")?
+            .owner;
+        Ok((owner != NONE).then_some(owner))
+    }
+
+    /// The name of `window`, if it has one.
+    pub fn window_name(&self, window: Window) -> anyhow::Result<Option<String>> {
+        let name = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 256)
+            .context("failed to get the window name")?
+            .reply()
+            .context("failed to get the window name")?
+            .value;
+        Ok((!name.is_empty()).then(|| String::from_utf8_lossy(&name).into_owned()))
+    }
+
+    /// The first value of `window`'s CARDINAL property `name`, if it has one.
+    pub fn cardinal(&self, window: Window, name: &str) -> anyhow::Result<Option<u32>> {
+        let reply = self
+            .conn
+            .get_property(false, window, self.intern(name)?, AtomEnum::CARDINAL, 0, 1)
+            .with_context(|| format!("failed to get {name}"))?
+            .reply()
+            .with_context(|| format!("failed to get {name}"))?;
+        Ok(reply.value32().and_then(|mut values| values.next()))
+    }
+
+    /// Ask the owner of `selection` to convert it to `target`. Returns None if there's no owner
+    /// or it refused.
+    pub fn convert(&self, selection: Atom, target: Atom) -> anyhow::Result<Option<Data>> {
+        if self.owner(selection)?.is_none() {
+            return Ok(None);
+        }
+        self.conn
+            .convert_selection(self.window, selection, target, self.property, CURRENT_TIME)
+            .context("failed to request the selection")?;
+        self.conn
+            .flush()
+            .context("failed to request the selection")?;
+
+        let notify = self.wait_for(|event| match event {
+            Event::SelectionNotify(event) if event.requestor == self.window => Some(event.property),
+            _ => None,
+        })?;
+        if notify == NONE {
+            return Ok(None);
+        }
+
+        let data = self.take_property()?;
+        if data.kind != self.incr {
+            return Ok(Some(data));
+        }
+
+        // large selections come in pieces, each one a new value of the property that we delete
+        // once we've read it, ending with an empty one
+        let mut data = Data {
+            kind: NONE,
+            format: 8,
+            bytes: Vec::new(),
+        };
+        loop {
+            self.wait_for(|event| match event {
+                Event::PropertyNotify(event)
+                    if event.window == self.window
+                        && event.atom == self.property
+                        && event.state == Property::NEW_VALUE =>
+                {
+                    Some(())
+                }
+                _ => None,
+            })?;
+            let piece = self.take_property()?;
+            if piece.bytes.is_empty() {
+                return Ok(Some(data));
+            }
+            data.kind = piece.kind;
+            data.format = piece.format;
+            data.bytes.extend_from_slice(&piece.bytes);
+        }
+    }
+
+    /// The contents of `selection` as UTF-8 text, or None if it's empty or isn't text.
+    pub fn text(&self, selection: Atom) -> anyhow::Result<Option<String>> {
+        let Some(data) = self.convert(selection, self.intern("UTF8_STRING")?)? else {
+            return Ok(None);
+        };
+        Ok(String::from_utf8(data.bytes).ok())
+    }
+
+    /// Read and delete our property.
+    fn take_property(&self) -> anyhow::Result<Data> {
+        let reply = self
+            .conn
+            .get_property(
+                true,
+                self.window,
+                self.property,
+                AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )
+            .context("failed to read the selection")?
+            .reply()
+            .context("failed to read the selection")?;
+        self.conn.flush().context("failed to read the selection")?;
+        Ok(Data {
+            kind: reply.type_,
+            format: reply.format,
+            bytes: reply.value,
+        })
+    }
+
+    fn wait_for<T>(&self, mut f: impl FnMut(Event) -> Option<T>) -> anyhow::Result<T> {
+        let start = Instant::now();
+        loop {
+            match self.conn.poll_for_event().context("X connection failed")? {
+                Some(event) => {
+                    if let Some(found) = f(event) {
+                        return Ok(found);
+                    }
+                }
+                None if start.elapsed() > TIMEOUT => bail!("the selection owner didn't answer"),
+                None => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+}
+
+/// The clipboard contents as UTF-8 text, or None if it's empty or isn't text.
+pub fn read_text() -> anyhow::Result<Option<String>> {
+    let reader = Reader::connect()?;
+    reader.text(reader.selection(false)?)
+}
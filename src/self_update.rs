@@ -0,0 +1,215 @@
+//! `fw self-update`, replacing this fw with the latest release, for installs that don't come from
+//! a package manager.
+//!
+//! A release has a binary for each platform named `fw-ARCH-OS`, like `fw-x86_64-linux`, and a
+//! `SHA256SUMS` file covering them, with a minisign signature in `SHA256SUMS.minisig`. The
+//! signature is checked against `--public-key`, which defaults to `FW_UPDATE_PUBLIC_KEY` from
+//! when fw was built. Without a key nothing is installed unless `--insecure-no-signature` says
+//! to go ahead unchecked, since the release can come from anywhere with `--endpoint`.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Where the latest release is described, in the GitHub API's format.
+const RELEASES: &str = "https://api.github.com/repos/aswild/fixedwidth/releases/latest";
+
+/// The most that one download of a release asset may be.
+const MAX_DOWNLOAD: u64 = 256 * 1024 * 1024;
+
+pub fn command() -> Command {
+    Command::new("self-update")
+        .about("Update fw to the latest release")
+        .long_about(
+            "Update fw to the latest release. The binary for this platform is downloaded, \
+             checked against the release's signed checksums, and put in place of the running \
+             fw in one step, so that a failed update leaves the old one working.",
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Only say whether there's a newer release"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("check")
+                .help("Install the latest release even if it isn't newer than this fw"),
+        )
+        .arg(
+            Arg::new("public-key")
+                .long("public-key")
+                .value_name("KEY")
+                .default_value(option_env!("FW_UPDATE_PUBLIC_KEY"))
+                .help("The base64 minisign public key that releases are signed with"),
+        )
+        .arg(
+            Arg::new("insecure-no-signature")
+                .long("insecure-no-signature")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("public-key")
+                .help("Install the release without checking its signature if there's no key"),
+        )
+        .arg(
+            Arg::new("endpoint")
+                .long("endpoint")
+                .value_name("URL")
+                .default_value(RELEASES)
+                .env("FW_UPDATE_URL")
+                .hide(true)
+                .help("Where to look up the latest release"),
+        )
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> anyhow::Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| anyhow!("release {} has no {name}", self.tag_name))
+    }
+}
+
+/// The numeric parts of a version like `v1.2.3`, for comparing.
+fn version(version: &str) -> Vec<u64> {
+    let version = version.trim_start_matches('v');
+    let version = version.split(['-', '+']).next().unwrap_or_default();
+    version.split('.').map(|n| n.parse().unwrap_or(0)).collect()
+}
+
+fn download(agent: &ureq::Agent, asset: &Asset) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    agent
+        .get(&asset.browser_download_url)
+        .call()
+        .map_err(anyhow::Error::from)
+        .and_then(|response| {
+            response
+                .into_reader()
+                .take(MAX_DOWNLOAD + 1)
+                .read_to_end(&mut data)
+                .map_err(anyhow::Error::from)
+        })
+        .with_context(|| format!("failed to download {}", asset.name))?;
+    if data.len() as u64 > MAX_DOWNLOAD {
+        bail!("{} is over {MAX_DOWNLOAD} bytes", asset.name);
+    }
+    Ok(data)
+}
+
+/// The checksum for `name` from the lines of `sha256sum` output in `sums`.
+fn checksum<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+    sums.lines().find_map(|line| {
+        let (sum, file) = line.split_once(char::is_whitespace)?;
+        // sha256sum marks binary mode files with a *
+        (file.trim_start().trim_start_matches('*') == name).then_some(sum)
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Put `data` in place of the executable at `exe`. The new binary is written next to it and
+/// renamed over it, so that nothing ever sees a half-written fw.
+fn replace(exe: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let name = exe.file_name().unwrap_or_default().to_string_lossy();
+    let temp = exe.with_file_name(format!(".{name}.update-{}", std::process::id()));
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o755)
+            .open(&temp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&temp, exe)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result.with_context(|| format!("failed to replace {}", exe.display()))
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let agent = ureq::AgentBuilder::new()
+        .user_agent(concat!("fw/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(60))
+        .build();
+    let endpoint = args.get_one::<String>("endpoint").unwrap();
+    let release: Release = agent
+        .get(endpoint)
+        .call()
+        .map_err(anyhow::Error::from)
+        .and_then(|response| Ok(response.into_json()?))
+        .with_context(|| format!("failed to check for updates at {endpoint}"))?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = release.tag_name.trim_start_matches('v');
+    if version(latest) <= version(current) && !args.get_flag("force") {
+        println!("fw {current} is up to date");
+        return Ok(());
+    }
+    if args.get_flag("check") {
+        println!("fw {latest} is available, this is {current}");
+        return Ok(());
+    }
+
+    let name = format!("fw-{}-{}", env::consts::ARCH, env::consts::OS);
+    let binary = release.asset(&name)?;
+    let sums = download(&agent, release.asset("SHA256SUMS")?)?;
+    match args.get_one::<String>("public-key") {
+        Some(key) => {
+            let key = PublicKey::from_base64(key).context("invalid public key")?;
+            let signature = download(&agent, release.asset("SHA256SUMS.minisig")?)?;
+            String::from_utf8(signature)
+                .map_err(anyhow::Error::from)
+                .and_then(|signature| Ok(Signature::decode(&signature)?))
+                .and_then(|signature| Ok(key.verify(&sums, &signature, false)?))
+                .context("the release's checksums aren't signed by the public key")?;
+        }
+        None if args.get_flag("insecure-no-signature") => tracing::warn!(
+            "not checking the release's signature, since no --public-key was given and fw was \
+             built without FW_UPDATE_PUBLIC_KEY"
+        ),
+        None => bail!(
+            "can't check the release's signature, since no --public-key was given and fw was \
+             built without FW_UPDATE_PUBLIC_KEY (--insecure-no-signature installs it anyway)"
+        ),
+    }
+    let sums = String::from_utf8(sums).context("SHA256SUMS is not valid UTF-8")?;
+    let expected = checksum(&sums, &name).with_context(|| format!("SHA256SUMS has no {name}"))?;
+
+    let data = download(&agent, binary)?;
+    let actual = hex(&Sha256::digest(&data));
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!("{name} doesn't match its checksum, expected {expected} but got {actual}");
+    }
+    let exe: PathBuf = env::current_exe().context("failed to find the fw executable")?;
+    replace(&exe, &data)?;
+    println!("updated {} from {current} to {latest}", exe.display());
+    Ok(())
+}
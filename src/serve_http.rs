@@ -0,0 +1,239 @@
+//! `fw serve-http`, converting text over HTTP for webhooks and chat bots.
+//!
+//! `POST /convert?style=NAME` converts the request body, and `GET /styles` lists the styles.
+//! Every response is JSON, with an `error` key for failures.
+//!
+//! SIGHUP reloads the config file, and SIGUSR1 copies the last converted text to the clipboard.
+
+use std::io::Read;
+use std::os::fd::OwnedFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Arg, ArgMatches, Command};
+use fw::style::Style;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::chain;
+use crate::clipboard;
+use crate::config::Config;
+use crate::signals;
+
+/// The most text one request can convert.
+const MAX_BODY: u64 = 1024 * 1024;
+
+pub fn command() -> Command {
+    clipboard::add_args(Command::new("serve-http"))
+        .about("Convert text sent over HTTP")
+        .long_about(
+            "Convert text sent over HTTP. 'POST /convert' converts the request body and \
+             responds with {\"style\": ..., \"text\": ...}. The query parameters 'style', \
+             'decode=true', and 'seed' work like the options of the same names. 'GET /styles' \
+             lists the styles, including the ones from the config file. \
+             Send fw SIGHUP to reload the config file, or SIGUSR1 to copy the text from the \
+             last conversion to the clipboard.",
+        )
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .value_name("ADDR")
+                .default_value("127.0.0.1:8080")
+                .help("The address and port to listen on"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .env("FW_CONFIG")
+                .help("Read the styles defined in FILE instead of the usual config file"),
+        )
+}
+
+/// Decode `%XX` escapes and `+` in a query string component.
+fn unescape(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => match rest
+                .get(..2)
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            {
+                Some(byte) => {
+                    bytes.push(byte);
+                    rest = &rest[2..];
+                }
+                None => bytes.push(b'%'),
+            },
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The value of the query parameter `key` in `url`, if there is one.
+fn query(url: &str, key: &str) -> Option<String> {
+    let (_, query) = url.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        (unescape(k) == key).then(|| unescape(v))
+    })
+}
+
+/// What the request loop shares with the signal handling thread.
+#[derive(Default)]
+struct State {
+    config: RwLock<Config>,
+    /// The text from the last conversion, for SIGUSR1.
+    last: Mutex<Option<String>>,
+}
+
+/// Answer a `POST /convert`.
+fn convert(state: &State, request: &mut Request) -> Result<Value, (u16, String)> {
+    let url = request.url().to_owned();
+    let name = query(&url, "style").unwrap_or_else(|| "fullwidth".to_owned());
+    let config = state.config.read().unwrap();
+    let mut style = chain::find(&config, &name).map_err(|err| (400, format!("{err:#}")))?;
+    drop(config);
+    if let Some(seed) = query(&url, "seed") {
+        let seed: u64 = seed
+            .parse()
+            .map_err(|_| (400, format!("invalid seed '{seed}'")))?;
+        style = style.map(|style| style.with_seed(seed));
+    }
+    let decode = match query(&url, "decode").as_deref() {
+        None | Some("false" | "0") => false,
+        Some("" | "true" | "1") => true,
+        Some(value) => return Err((400, format!("invalid decode '{value}'"))),
+    };
+
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| (400, format!("failed to read the request: {err}")))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err((413, format!("the text is over {MAX_BODY} bytes")));
+    }
+    let text = String::from_utf8(body).map_err(|_| (400, "the text is not valid UTF-8".into()))?;
+    let text = if decode {
+        style.decode_with(&text, Style::decode)
+    } else {
+        style.apply(&text)
+    };
+    *state.last.lock().unwrap() = Some(text.clone());
+    Ok(json!({ "style": name, "text": text }))
+}
+
+/// Answer a `GET /styles`.
+fn styles(config: &Config) -> Value {
+    let styles: Vec<Value> = chain::names(config)
+        .into_iter()
+        .map(|name| {
+            let description = match Style::from_name(name) {
+                Some(style) => style.description(),
+                None => &config.styles[name].description,
+            };
+            json!({ "name": name, "description": description })
+        })
+        .collect();
+    json!({ "styles": styles })
+}
+
+fn respond(state: &State, mut request: Request) -> anyhow::Result<()> {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let result = match (request.method(), path) {
+        (Method::Post, "/convert") => convert(state, &mut request),
+        (Method::Get, "/styles") => Ok(styles(&state.config.read().unwrap())),
+        (_, "/convert" | "/styles") => Err((405, "method not allowed".to_owned())),
+        _ => Err((404, "not found".to_owned())),
+    };
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, json!({ "error": error })),
+    };
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    request
+        .respond(
+            Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(header),
+        )
+        .context("failed to send the response")
+}
+
+/// Handle SIGHUP and SIGUSR1 as they come in, forever.
+fn handle_signals(args: &ArgMatches, state: &State, pipe: &OwnedFd) -> anyhow::Result<()> {
+    let path = args.get_one::<PathBuf>("config").map(PathBuf::as_path);
+    loop {
+        for signal in signals::wait(pipe)? {
+            match signal {
+                libc::SIGHUP => match Config::load(path) {
+                    Ok(config) => {
+                        *state.config.write().unwrap() = config;
+                        tracing::info!("reloaded the config file");
+                    }
+                    // keep serving with the old one until it's fixed
+                    Err(err) => tracing::error!("{err:#}"),
+                },
+                libc::SIGUSR1 => {
+                    let last = state.last.lock().unwrap().clone();
+                    match last {
+                        Some(text) => {
+                            if let Err(err) = clipboard::copy(args, &text) {
+                                tracing::error!("{err:#}");
+                            }
+                        }
+                        None => tracing::warn!("nothing has been converted yet"),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    clipboard::check_args(args)?;
+    // a holder in this process would take over the signals, and stop us serving while it waits
+    if args.get_flag("foreground-wait")
+        || args.get_one::<String>("wait-mode").map(String::as_str) == Some("foreground")
+    {
+        bail!("serve-http can't hold the clipboard in the foreground");
+    }
+    let config = Config::load(args.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let state = Arc::new(State {
+        config: RwLock::new(config),
+        ..State::default()
+    });
+    let pipe = signals::catch(&[libc::SIGHUP, libc::SIGUSR1])?;
+    {
+        let (args, state) = (args.clone(), Arc::clone(&state));
+        thread::spawn(move || {
+            if let Err(err) = handle_signals(&args, &state, &pipe) {
+                tracing::error!("{err:#}");
+            }
+        });
+    }
+
+    let addr = args.get_one::<String>("listen").unwrap();
+    let server = Server::http(addr).map_err(|err| anyhow!("failed to listen on {addr}: {err}"))?;
+    match server.server_addr().to_ip() {
+        Some(addr) => tracing::info!("listening on http://{addr}"),
+        None => tracing::info!("listening on {addr}"),
+    }
+    for request in server.incoming_requests() {
+        // a client going away mid-response is their problem, not a reason to stop serving
+        if let Err(err) = respond(&state, request) {
+            tracing::error!("{err:#}");
+        }
+    }
+    Ok(())
+}
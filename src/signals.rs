@@ -0,0 +1,90 @@
+//! Turning signals into data on a pipe, so that they can be handled outside of a signal handler
+//! along with everything else a loop is waiting for.
+
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use anyhow::Context;
+
+/// The write end of the pipe that [`catch`] returns the read end of.
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    // SAFETY: write is async-signal-safe, and the pipe is non-blocking so this can't hang if
+    // signals pile up faster than they're read. Signal numbers all fit in a byte. A failed write
+    // sets errno, which is put back for the code that the signal interrupted.
+    unsafe {
+        let errno = *libc::__errno_location();
+        libc::write(
+            SIGNAL_PIPE.load(Ordering::Relaxed),
+            [signal as u8].as_ptr().cast(),
+            1,
+        );
+        *libc::__errno_location() = errno;
+    }
+}
+
+/// Write the number of each of `signals` to the returned pipe as a byte when it arrives, for
+/// [`drain`] or [`wait`]. There's only one pipe per process, so this should only be called once.
+pub fn catch(signals: &[libc::c_int]) -> anyhow::Result<OwnedFd> {
+    let mut fds = [0; 2];
+    // SAFETY: fds has room for the two fds
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } < 0 {
+        return Err(io::Error::last_os_error()).context("failed to create a pipe");
+    }
+    // the write end stays open for as long as the handlers are installed, which is forever
+    SIGNAL_PIPE.store(fds[1], Ordering::Relaxed);
+    // SAFETY: pipe2 just gave us this fd
+    let read = unsafe { OwnedFd::from_raw_fd(fds[0]) };
+
+    for &signal in signals {
+        // SAFETY: a zeroed sigaction is valid, and on_signal only does async-signal-safe things
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_signal as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(signal, &action, ptr::null_mut()) < 0 {
+                return Err(io::Error::last_os_error()).context("failed to install signal handler");
+            }
+        }
+    }
+    Ok(read)
+}
+
+/// The signals that have arrived on `pipe` from [`catch`] since the last call, without waiting.
+pub fn drain(pipe: &OwnedFd) -> Vec<libc::c_int> {
+    let mut buf = [0u8; 64];
+    // SAFETY: reading into a buffer of the right size from a non-blocking pipe
+    let n = unsafe { libc::read(pipe.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+    buf[..n.max(0) as usize]
+        .iter()
+        .map(|&signal| signal.into())
+        .collect()
+}
+
+/// Wait for at least one signal to arrive on `pipe` from [`catch`], and return all of them.
+#[cfg(feature = "http")]
+pub fn wait(pipe: &OwnedFd) -> anyhow::Result<Vec<libc::c_int>> {
+    loop {
+        let mut fd = libc::pollfd {
+            fd: pipe.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: fd is a single valid pollfd
+        if unsafe { libc::poll(&mut fd, 1, -1) } < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err).context("failed to wait for signals");
+            }
+        }
+        let signals = drain(pipe);
+        if !signals.is_empty() {
+            return Ok(signals);
+        }
+    }
+}
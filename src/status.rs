@@ -0,0 +1,74 @@
+//! `fw status`, reporting on the fw holding the clipboard in the background.
+//!
+//! The holder keeps its state in properties on its own window (see [`holder`]), so there are no
+//! PID files to go stale: if the window owns a selection, the holder is running.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{ArgMatches, Command};
+use fw::layout;
+
+use crate::holder;
+use crate::selection::Reader;
+
+/// How many columns of the held text to show.
+const PREVIEW_COLS: usize = 40;
+
+pub fn command() -> Command {
+    Command::new("status").about("Show whether fw is holding the clipboard, and what it holds")
+}
+
+pub fn run(_args: &ArgMatches) -> anyhow::Result<()> {
+    let reader = Reader::connect()?;
+    let mut found = false;
+    for (name, primary) in [("CLIPBOARD", false), ("PRIMARY", true)] {
+        let selection = reader.selection(primary)?;
+        let Some(window) = holder::find(&reader, selection)? else {
+            continue;
+        };
+        found = true;
+
+        let pid = reader.cardinal(window, "_NET_WM_PID")?;
+        let started = reader.cardinal(window, holder::STARTED_PROPERTY)?;
+        print!("fw holds {name}");
+        if let Some(pid) = pid {
+            print!(" in process {pid}");
+        }
+        if let Some(started) = started {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            print!(", for {}", age(now.saturating_sub(started.into())));
+        }
+        println!();
+        match reader.text(selection)? {
+            Some(text) => println!("  {}", preview(&text)),
+            None => println!("  (not text)"),
+        }
+    }
+    if !found {
+        println!("fw isn't holding the clipboard");
+    }
+    Ok(())
+}
+
+/// The first line of `text`, cut short to fit [`PREVIEW_COLS`].
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    let (kept, _) = layout::truncate_line(line, PREVIEW_COLS);
+    if kept.len() < text.len() {
+        format!("{kept}…")
+    } else {
+        kept.to_owned()
+    }
+}
+
+/// `secs` as a rough human readable duration, like "2h 5m".
+fn age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, secs / 60 % 60),
+        _ => format!("{}d {}h", secs / 86400, secs / 3600 % 24),
+    }
+}
@@ -0,0 +1,225 @@
+//! Streaming conversion from stdin to stdout, for inputs too big to comfortably hold in memory.
+//!
+//! Stdin is read in chunks and converted a batch of whole lines at a time, so memory use is
+//! bounded by the chunk size and the longest line rather than by the size of the input. Splitting
+//! only at line breaks also means a batch never ends in the middle of a UTF-8 sequence or a
+//! grapheme cluster. With more than one job, each batch is split again into runs of lines that
+//! are converted in parallel.
+
+use std::io::{self, BufWriter, ErrorKind, Read, StdinLock, StdoutLock, Write};
+
+use anyhow::Context;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::ThreadPoolBuilder;
+
+use crate::input::LineEndings;
+use crate::lossy;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A run of lines to convert, and the line ending that came after it.
+type Piece = (String, Option<&'static str>);
+
+struct Stream<'a> {
+    stdin: StdinLock<'a>,
+    stdout: BufWriter<StdoutLock<'a>>,
+    line_endings: LineEndings,
+    /// Whether invalid UTF-8 is passed through with [`lossy`] rather than being an error.
+    lossy: bool,
+    /// Everything written so far, if it's going to be copied to the clipboard.
+    collected: Option<String>,
+    buf: Vec<u8>,
+    /// The line ending after the previous batch, held back until we know whether it's the
+    /// trailing newline.
+    pending: Option<&'static str>,
+    converted_any: bool,
+}
+
+impl Stream<'_> {
+    fn write(&mut self, text: &str) -> anyhow::Result<()> {
+        if let Some(collected) = &mut self.collected {
+            collected.push_str(text);
+        }
+        let bytes = if self.lossy {
+            lossy::encode(text)
+        } else {
+            text.as_bytes().into()
+        };
+        self.stdout
+            .write_all(&bytes)
+            .context("failed to write output")
+    }
+
+    fn write_line_ending(&mut self, ending: &str) -> anyhow::Result<()> {
+        self.write(&self.line_endings.apply(ending.to_owned()))
+    }
+
+    /// Read up to `size` more bytes into the buffer. Returns true at the end of the input.
+    fn fill(&mut self, size: usize) -> anyhow::Result<bool> {
+        let start = self.buf.len();
+        self.buf.resize(start + size, 0);
+        let n = loop {
+            match self.stdin.read(&mut self.buf[start..]) {
+                Ok(n) => break n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err).context("failed to read stdin"),
+            }
+        };
+        self.buf.truncate(start + n);
+        Ok(n == 0)
+    }
+
+    /// Convert and write everything up to the end of the input. `eof` says whether the buffer
+    /// already holds the last of it. Batches are split into `jobs` pieces, which
+    /// `convert_pieces` converts.
+    fn run(
+        &mut self,
+        mut eof: bool,
+        jobs: usize,
+        mut convert_pieces: impl FnMut(Vec<Piece>) -> anyhow::Result<Vec<String>>,
+    ) -> anyhow::Result<()> {
+        loop {
+            let end = if eof {
+                self.buf.len()
+            } else {
+                match self.buf.iter().rposition(|&b| b == b'\n') {
+                    Some(i) => i + 1,
+                    None => {
+                        eof = self.fill(CHUNK_SIZE * jobs)?;
+                        continue;
+                    }
+                }
+            };
+            if eof && end == 0 && self.converted_any {
+                return Ok(());
+            }
+
+            let rest = self.buf.split_off(end);
+            let bytes = std::mem::replace(&mut self.buf, rest);
+            let mut batch = if self.lossy {
+                lossy::decode(&bytes)
+            } else {
+                String::from_utf8(bytes).context(
+                    "stdin is not valid UTF-8 (use --lossy to pass invalid bytes through)",
+                )?
+            };
+            let ending = split_line_ending(&mut batch);
+            if let Some(ending) = self.pending.take() {
+                self.write_line_ending(ending)?;
+            }
+            let pieces = split_lines(batch, jobs);
+            let endings: Vec<_> = pieces.iter().map(|(_, ending)| *ending).collect();
+            for (text, ending) in convert_pieces(pieces)?.iter().zip(endings) {
+                self.write(text)?;
+                if let Some(ending) = ending {
+                    self.write_line_ending(ending)?;
+                }
+            }
+            self.converted_any = true;
+            self.pending = ending;
+            if eof {
+                return Ok(());
+            }
+            eof = self.fill(CHUNK_SIZE * jobs)?;
+        }
+    }
+}
+
+/// Split the last line ending off of `batch`.
+fn split_line_ending(batch: &mut String) -> Option<&'static str> {
+    if batch.ends_with("\r\n") {
+        batch.truncate(batch.len() - 2);
+        Some("\r\n")
+    } else if batch.ends_with('\n') {
+        batch.pop();
+        Some("\n")
+    } else {
+        None
+    }
+}
+
+/// Split `batch` at line breaks into at most `n` runs of lines of roughly the same size.
+fn split_lines(mut batch: String, n: usize) -> Vec<Piece> {
+    if n <= 1 {
+        return vec![(batch, None)];
+    }
+    let target = batch.len().div_ceil(n).max(1);
+    let mut pieces = Vec::with_capacity(n);
+    while batch.len() > target {
+        // searching bytes rather than chars since target may be inside a UTF-8 sequence
+        let Some(i) = batch.as_bytes()[target..].iter().position(|&b| b == b'\n') else {
+            break;
+        };
+        let rest = batch.split_off(target + i + 1);
+        let mut piece = std::mem::replace(&mut batch, rest);
+        let ending = split_line_ending(&mut piece);
+        pieces.push((piece, ending));
+    }
+    pieces.push((batch, None));
+    pieces
+}
+
+/// Convert stdin to stdout with `convert`, one batch of lines at a time, using up to `jobs`
+/// threads. The result is the same as converting all of stdin at once, including the handling of
+/// the trailing newline. If `collect` is set, the whole output is also returned so that it can
+/// be copied, with any [`lossy`] placeholders still in it.
+pub fn run(
+    line_endings: LineEndings,
+    keep_trailing_newline: bool,
+    lossy: bool,
+    collect: bool,
+    jobs: usize,
+    convert: impl Fn(String) -> anyhow::Result<String> + Sync,
+) -> anyhow::Result<Option<String>> {
+    let mut stream = Stream {
+        stdin: io::stdin().lock(),
+        stdout: BufWriter::new(io::stdout().lock()),
+        line_endings,
+        lossy,
+        collected: collect.then(String::new),
+        buf: Vec::with_capacity(CHUNK_SIZE),
+        pending: None,
+        converted_any: false,
+    };
+
+    // most input is a line or two, which isn't worth starting threads for
+    let eof = stream.fill(CHUNK_SIZE)?;
+    if eof || jobs <= 1 {
+        stream.run(eof, 1, |pieces| {
+            pieces.into_iter().map(|(text, _)| convert(text)).collect()
+        })?;
+    } else {
+        // a scoped pool joins its threads before returning, so none are left idling while we
+        // copy and wait on the clipboard
+        ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_scoped(
+                |thread| thread.run(),
+                |pool| {
+                    stream.run(eof, jobs, |pieces| {
+                        pool.install(|| {
+                            pieces
+                                .into_par_iter()
+                                .map(|(text, _)| convert(text))
+                                .collect()
+                        })
+                    })
+                },
+            )
+            .context("failed to start conversion threads")??;
+    }
+
+    if keep_trailing_newline {
+        if let Some(ending) = stream.pending {
+            stream.write_line_ending(ending)?;
+        }
+    } else {
+        // the terminator isn't part of the text, so don't copy it
+        stream
+            .stdout
+            .write_all(line_endings.terminator().as_bytes())
+            .context("failed to write output")?;
+    }
+    stream.stdout.flush().context("failed to write output")?;
+    Ok(stream.collected)
+}
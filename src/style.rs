@@ -0,0 +1,765 @@
+//! The text styles fw can convert to.
+//!
+//! Everything here works on grapheme clusters rather than chars, so that combining marks stay on
+//! their base character and emoji sequences like `👨‍👩‍👧` or `🇯🇵` are never split apart.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+mod chain;
+mod homoglyph;
+mod kana;
+mod morse;
+
+pub use chain::{Chain, Step};
+pub(crate) use homoglyph::Rng;
+
+pub const WIDE_SPACE: char = '\u{3000}';
+const FULLWIDTH_OFFSET: u32 = 0xFEE0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// ASCII to the Halfwidth and Fullwidth Forms block, e.g. `ｃａｔｅ`
+    Fullwidth,
+    /// Enclosed alphanumerics, e.g. `ⓒⓐⓣⓔ`
+    Circled,
+    /// Mathematical bold letters and digits, e.g. `𝐜𝐚𝐭𝐞`
+    MathBold,
+    /// A space between every character, e.g. `c a t e`
+    Spaced,
+    /// Each line backwards, e.g. `etac`
+    Reversed,
+    /// Cyrillic and Greek lookalikes of the ASCII letters, e.g. `саtе`. With a seed, each letter
+    /// gets a random one of its lookalikes rather than always the closest.
+    Homoglyph { seed: Option<u64> },
+    /// Leet speak, e.g. `c473`. Level 1 only replaces vowels, and levels 2 and 3 replace more
+    /// letters each.
+    Leet { level: u8 },
+    /// Braille patterns, e.g. `⠉⠁⠞⠑`. Everything up to `_` in ASCII has a cell in North American
+    /// computer braille, and capitals add the eighth dot.
+    Braille,
+    /// Morse code, e.g. `-.-. .- - .`
+    Morse(MorseSymbols),
+}
+
+/// The characters morse code is written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MorseSymbols {
+    pub dot: char,
+    pub dash: char,
+    /// Written in place of each space in the text.
+    pub word: char,
+    /// Between the letters, and on both sides of `word`.
+    pub letter: char,
+}
+
+impl MorseSymbols {
+    pub const ASCII: MorseSymbols = MorseSymbols {
+        dot: '.',
+        dash: '-',
+        word: '/',
+        letter: ' ',
+    };
+
+    /// Fullwidth symbols, which line up with text converted to fullwidth.
+    pub const FULLWIDTH: MorseSymbols = MorseSymbols {
+        dot: '．',
+        dash: '－',
+        word: '／',
+        letter: WIDE_SPACE,
+    };
+
+    /// A preset by name, ascii or fullwidth, or the dot, dash, and optionally the word and letter
+    /// separators as a string of two to four characters.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec {
+            "ascii" => return Ok(Self::ASCII),
+            "fullwidth" => return Ok(Self::FULLWIDTH),
+            _ => (),
+        }
+        let chars: Vec<char> = spec.chars().collect();
+        let [dot, dash, ref rest @ ..] = chars[..] else {
+            return Err("expected ascii, fullwidth, or two to four characters".into());
+        };
+        let (word, letter) = match *rest {
+            [] => (Self::ASCII.word, Self::ASCII.letter),
+            [word] => (word, Self::ASCII.letter),
+            [word, letter] => (word, letter),
+            _ => return Err("expected ascii, fullwidth, or two to four characters".into()),
+        };
+        let symbols = Self {
+            dot,
+            dash,
+            word,
+            letter,
+        };
+        let mut all = [dot, dash, word, letter];
+        all.sort_unstable();
+        if all.windows(2).any(|w| w[0] == w[1]) {
+            return Err("the morse symbols must all be different".into());
+        }
+        Ok(symbols)
+    }
+}
+
+impl Style {
+    pub const ALL: [Style; 9] = [
+        Style::Fullwidth,
+        Style::Circled,
+        Style::MathBold,
+        Style::Spaced,
+        Style::Reversed,
+        Style::Homoglyph { seed: None },
+        Style::Leet { level: 2 },
+        Style::Braille,
+        Style::Morse(MorseSymbols::ASCII),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Fullwidth => "fullwidth",
+            Style::Circled => "circled",
+            Style::MathBold => "math-bold",
+            Style::Spaced => "spaced",
+            Style::Reversed => "reversed",
+            Style::Homoglyph { .. } => "homoglyph",
+            Style::Leet { .. } => "leet",
+            Style::Braille => "braille",
+            Style::Morse(_) => "morse",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// A short description of the style, for `fw styles`.
+    pub fn description(self) -> &'static str {
+        match self {
+            Style::Fullwidth => "ASCII to the Halfwidth and Fullwidth Forms block",
+            Style::Circled => "Letters and digits in circles",
+            Style::MathBold => "Mathematical bold letters and digits",
+            Style::Spaced => "A space between every character",
+            Style::Reversed => "Each line backwards",
+            Style::Homoglyph { .. } => "Cyrillic and Greek lookalikes of the ASCII letters",
+            Style::Leet { .. } => "Leet speak, with digits and symbols for letters",
+            Style::Braille => "Braille patterns from North American computer braille",
+            Style::Morse(_) => "Morse code, in dots and dashes",
+        }
+    }
+
+    /// This style with its random choices made from `seed`, for `--seed`. Styles that don't
+    /// make any are returned as-is.
+    pub fn with_seed(self, seed: u64) -> Self {
+        match self {
+            Style::Homoglyph { .. } => Style::Homoglyph { seed: Some(seed) },
+            _ => self,
+        }
+    }
+
+    /// This style at `level`, for `--leet-level`. Styles without levels are returned as-is.
+    pub fn with_level(self, level: u8) -> Self {
+        match self {
+            Style::Leet { .. } => Style::Leet {
+                level: level.clamp(1, LEET_LEVELS),
+            },
+            _ => self,
+        }
+    }
+
+    /// This style written with `symbols`, for `--morse-symbols`. Other styles are returned
+    /// as-is.
+    pub fn with_morse_symbols(self, symbols: MorseSymbols) -> Self {
+        match self {
+            Style::Morse(_) => Style::Morse(symbols),
+            _ => self,
+        }
+    }
+
+    /// Convert `text` to this style.
+    pub fn apply(self, text: &str) -> String {
+        self.apply_classes(text, Classes::ALL)
+    }
+
+    /// Convert only the characters of `text` in `classes` to this style, for `--only` and
+    /// `--except`. Styles that don't map characters, like spacing, ignore `classes`.
+    pub fn apply_classes(mut self, text: &str, classes: Classes) -> String {
+        self.apply_classes_mut(text, classes)
+    }
+
+    /// Like [`apply_classes`](Self::apply_classes), but carry this style's random choices on
+    /// from one call to the next, so that converting text in parts picks the same as converting
+    /// it all at once.
+    pub fn apply_classes_mut(&mut self, text: &str, classes: Classes) -> String {
+        let mut out = String::with_capacity(self.output_capacity(text.len()));
+        self.apply_to_mut(text, classes, &mut out);
+        out
+    }
+
+    /// Like [`apply_classes`](Self::apply_classes), but append the result to `out`. This lets
+    /// callers reuse one buffer across many conversions.
+    pub fn apply_to(mut self, text: &str, classes: Classes, out: &mut String) {
+        self.apply_to_mut(text, classes, out)
+    }
+
+    /// Like [`apply_to`](Self::apply_to), with the random choices carried on like
+    /// [`apply_classes_mut`](Self::apply_classes_mut).
+    pub fn apply_to_mut(&mut self, text: &str, classes: Classes, out: &mut String) {
+        match *self {
+            Style::Fullwidth => fullwidth(text, classes, out),
+            Style::Circled => map_graphemes(text, circled_char, classes, out),
+            Style::MathBold => map_graphemes(text, math_bold_char, classes, out),
+            Style::Homoglyph { seed } => {
+                let mut rng = seed.map(homoglyph::Rng::new);
+                let pick = |c| match (homoglyph::lookalikes(c), rng.as_mut()) {
+                    ([], _) => c,
+                    (lookalikes, Some(rng)) => lookalikes[rng.below(lookalikes.len())],
+                    (lookalikes, None) => lookalikes[0],
+                };
+                map_graphemes(text, pick, classes, out);
+                *self = Style::Homoglyph {
+                    seed: rng.map(homoglyph::Rng::into_seed),
+                };
+            }
+            Style::Leet { level } => map_graphemes(text, leet_fn(level), classes, out),
+            Style::Braille => map_graphemes(text, braille_char, classes, out),
+            Style::Morse(symbols) => morse::encode(text, symbols, out),
+            Style::Spaced => {
+                let mut after_break = true;
+                for g in text.graphemes(true) {
+                    let is_break = g.ends_with('\n');
+                    // don't space out line breaks, that just makes every line start with a space
+                    if !after_break && !is_break {
+                        out.push(' ');
+                    }
+                    out.push_str(g);
+                    after_break = is_break;
+                }
+            }
+            Style::Reversed => {
+                for line in text.split_inclusive('\n') {
+                    // keep the line ending at the end rather than reversing it to the front
+                    let body = line
+                        .strip_suffix("\r\n")
+                        .or_else(|| line.strip_suffix('\n'))
+                        .unwrap_or(line);
+                    out.extend(body.graphemes(true).rev());
+                    out.push_str(&line[body.len()..]);
+                }
+            }
+        }
+    }
+
+    /// How many bytes converting `len` bytes of ASCII to this style takes, which is enough to
+    /// pre-size the output for nearly any input.
+    pub fn output_capacity(self, len: usize) -> usize {
+        match self {
+            // 1 byte to 3, or 4 for the astral math letters
+            Style::Fullwidth | Style::Circled | Style::Homoglyph { .. } | Style::Braille => len * 3,
+            Style::MathBold => len * 4,
+            Style::Spaced => len * 2,
+            Style::Reversed | Style::Leet { .. } => len,
+            // four symbols and a separator for most letters
+            Style::Morse(_) => len * 5,
+        }
+    }
+
+    /// Whether this style converts each character on its own, rather than laying the text out
+    /// like spacing or reversing it does. Only these can be [mixed](mix).
+    pub fn maps_chars(self) -> bool {
+        self.decode_table().is_some()
+    }
+
+    /// Convert text in this style back to ASCII, for `--decode`. Anything that isn't part of the
+    /// style is left as-is.
+    pub fn decode(self, text: &str) -> String {
+        self.decode_classes(text, Classes::ALL)
+    }
+
+    /// The map from this style's characters back to ASCII, for the styles that map characters.
+    fn decode_table(self) -> Option<&'static HashMap<char, char>> {
+        static FULLWIDTH: OnceLock<HashMap<char, char>> = OnceLock::new();
+        static CIRCLED: OnceLock<HashMap<char, char>> = OnceLock::new();
+        static MATH_BOLD: OnceLock<HashMap<char, char>> = OnceLock::new();
+        static HOMOGLYPH: OnceLock<HashMap<char, char>> = OnceLock::new();
+        static BRAILLE: OnceLock<HashMap<char, char>> = OnceLock::new();
+        static LEET: [OnceLock<HashMap<char, char>>; LEET_LEVELS as usize] =
+            [const { OnceLock::new() }; LEET_LEVELS as usize];
+
+        match self {
+            Style::Fullwidth => Some(FULLWIDTH.get_or_init(|| decode_table(fw_char))),
+            Style::Circled => Some(CIRCLED.get_or_init(|| decode_table(circled_char))),
+            Style::MathBold => Some(MATH_BOLD.get_or_init(|| decode_table(math_bold_char))),
+            // every lookalike decodes, whichever one the seed picked
+            Style::Homoglyph { .. } => Some(HOMOGLYPH.get_or_init(|| {
+                (' '..='~')
+                    .flat_map(|c| homoglyph::lookalikes(c).iter().map(move |&l| (l, c)))
+                    .collect()
+            })),
+            Style::Leet { level } => {
+                Some(LEET[level as usize - 1].get_or_init(|| decode_table(leet_fn(level))))
+            }
+            Style::Braille => Some(BRAILLE.get_or_init(|| decode_table(braille_char))),
+            Style::Spaced | Style::Reversed | Style::Morse(_) => None,
+        }
+    }
+
+    /// Decode only the characters that decode to one of `classes`.
+    pub fn decode_classes(self, text: &str, classes: Classes) -> String {
+        let table = match (self.decode_table(), self) {
+            (Some(table), _) => table,
+            (None, Style::Spaced) => {
+                let mut out = String::with_capacity(text.len() / 2);
+                let mut graphemes = text.graphemes(true).peekable();
+                while let Some(g) = graphemes.next() {
+                    out.push_str(g);
+                    // apply put a space between every pair of graphemes that aren't line breaks
+                    if !g.ends_with('\n') && graphemes.peek().is_some_and(|n| !n.ends_with('\n')) {
+                        graphemes.next();
+                    }
+                }
+                return out;
+            }
+            (None, Style::Morse(symbols)) => return morse::decode(text, symbols),
+            // reversing twice is where we started
+            (None, _) => return self.apply(text),
+        };
+        text.chars()
+            .map(|c| match table.get(&c) {
+                Some(&decoded) if classes.contains(decoded) => decoded,
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Whether `c` looks like it was already converted: it's a fullwidth form, or one of this
+    /// style's own characters.
+    pub fn is_converted(self, c: char) -> bool {
+        [Style::Fullwidth, self]
+            .iter()
+            .filter_map(|style| style.decode_table())
+            .any(|table| table.contains_key(&c))
+    }
+
+    /// Decode anything in `text` that [`is_converted`](Self::is_converted) back to ASCII, for
+    /// `--decode-first`. Converting the result gives the same output for text that was already
+    /// partly converted as for plain ASCII.
+    pub fn decode_converted(self, text: &str, classes: Classes) -> String {
+        let text = Style::Fullwidth.decode_classes(text, classes);
+        match self.decode_table() {
+            Some(_) if self != Style::Fullwidth => self.decode_classes(&text, classes),
+            _ => text,
+        }
+    }
+
+    /// Convert `text` like [`apply_classes`](Self::apply_classes), but leave runs of graphemes
+    /// that [`is_converted`](Self::is_converted) as they are, for `--skip-converted`.
+    pub fn apply_unconverted(mut self, text: &str, classes: Classes) -> String {
+        self.apply_unconverted_mut(text, classes)
+    }
+
+    /// Like [`apply_unconverted`](Self::apply_unconverted), with the random choices carried on
+    /// like [`apply_classes_mut`](Self::apply_classes_mut).
+    pub fn apply_unconverted_mut(&mut self, text: &str, classes: Classes) -> String {
+        let mut out = String::with_capacity(self.output_capacity(text.len()));
+        let mut run_start = 0;
+        let mut run_converted = false;
+        for (i, g) in text.grapheme_indices(true) {
+            let converted = g.chars().next().is_some_and(|c| self.is_converted(c));
+            if converted != run_converted {
+                self.apply_run(&text[run_start..i], run_converted, classes, &mut out);
+                run_start = i;
+                run_converted = converted;
+            }
+        }
+        self.apply_run(&text[run_start..], run_converted, classes, &mut out);
+        out
+    }
+
+    fn apply_run(&mut self, run: &str, converted: bool, classes: Classes, out: &mut String) {
+        if converted {
+            out.push_str(run);
+        } else {
+            self.apply_to_mut(run, classes, out);
+        }
+    }
+}
+
+/// Convert each grapheme of `text` to the style that `pick` chooses for it. Only styles that
+/// [map characters](Style::maps_chars) give sensible results.
+pub fn mix(text: &str, classes: Classes, mut pick: impl FnMut(&str) -> Style) -> String {
+    let mut out = String::with_capacity(text.len() * 4);
+    for g in text.graphemes(true) {
+        pick(g).apply_to(g, classes, &mut out);
+    }
+    out
+}
+
+/// Random picks between styles for each grapheme, for `--chaos`. The picks carry on from one
+/// call to [`apply`](Self::apply) to the next.
+#[derive(Debug, Clone)]
+pub struct Chaos {
+    styles: Vec<Style>,
+    rng: Rng,
+}
+
+impl Chaos {
+    /// Pick from `styles`, which must not be empty, with choices made from `seed`.
+    pub fn new(styles: Vec<Style>, seed: u64) -> Self {
+        assert!(!styles.is_empty(), "no styles to pick from");
+        Self {
+            styles,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn apply(&mut self, text: &str, classes: Classes) -> String {
+        let Self { styles, rng } = self;
+        mix(text, classes, |_| styles[rng.below(styles.len())])
+    }
+}
+
+/// A set of character classes, for choosing which characters a style converts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Classes(u8);
+
+impl Classes {
+    /// ASCII letters, as well as halfwidth katakana.
+    pub const LETTERS: Classes = Classes(1 << 0);
+    pub const DIGITS: Classes = Classes(1 << 1);
+    /// ASCII punctuation and symbols, as well as halfwidth CJK punctuation.
+    pub const PUNCT: Classes = Classes(1 << 2);
+    pub const SPACE: Classes = Classes(1 << 3);
+    pub const NONE: Classes = Classes(0);
+    pub const ALL: Classes = Classes(0b1111);
+
+    pub const NAMES: [(&'static str, Classes); 4] = [
+        ("letters", Classes::LETTERS),
+        ("digits", Classes::DIGITS),
+        ("punct", Classes::PUNCT),
+        ("space", Classes::SPACE),
+    ];
+
+    /// Parse a comma-separated list of class names like `letters,punct`.
+    pub fn parse(list: &str) -> Result<Self, String> {
+        list.split(',')
+            .map(str::trim)
+            .try_fold(Classes::NONE, |classes, name| {
+                match Self::NAMES.iter().find(|(n, _)| *n == name) {
+                    Some(&(_, class)) => Ok(classes.union(class)),
+                    None => {
+                        let names: Vec<&str> = Self::NAMES.iter().map(|(n, _)| *n).collect();
+                        Err(format!(
+                            "unknown class '{name}', expected {}",
+                            names.join(", ")
+                        ))
+                    }
+                }
+            })
+    }
+
+    pub fn union(self, other: Classes) -> Classes {
+        Classes(self.0 | other.0)
+    }
+
+    pub fn complement(self) -> Classes {
+        Classes(!self.0 & Self::ALL.0)
+    }
+
+    pub fn intersection(self, other: Classes) -> Classes {
+        Classes(self.0 & other.0)
+    }
+
+    /// Whether `c` belongs to one of these classes. Characters outside of every class, like
+    /// control characters, are always in.
+    pub fn contains(self, c: char) -> bool {
+        let class = match c {
+            'a'..='z' | 'A'..='Z' | '\u{FF66}'..='\u{FF9F}' => Classes::LETTERS,
+            '0'..='9' => Classes::DIGITS,
+            ' ' => Classes::SPACE,
+            '!'..='~' | '\u{FF61}'..='\u{FF65}' => Classes::PUNCT,
+            _ => return true,
+        };
+        self.0 & class.0 != 0
+    }
+}
+
+/// Build the inverse of the char mapping `encode` over printable ASCII, so that decoding can
+/// never drift out of sync with encoding.
+fn decode_table(encode: fn(char) -> char) -> HashMap<char, char> {
+    (' '..='~')
+        .map(|c| (encode(c), c))
+        .filter(|(encoded, c)| encoded != c)
+        .collect()
+}
+
+/// Whether the grapheme cluster `g` is an emoji sequence, like the keycap `#️⃣`, a ZWJ sequence
+/// like `👨‍👩‍👧`, a flag, or an emoji with a skin tone. These are copied through as-is, since
+/// converting any part of them would break the emoji.
+fn is_emoji_cluster(g: &str) -> bool {
+    g.chars().nth(1).is_some()
+        && g.chars().any(|c| {
+            matches!(c,
+                '\u{FE0F}' | '\u{20E3}' | '\u{200D}'
+                | '\u{1F3FB}'..='\u{1F3FF}' // skin tones
+                | '\u{1F1E6}'..='\u{1F1FF}' // regional indicators
+                | '\u{E0020}'..='\u{E007F}' // tag sequences
+            )
+        })
+}
+
+/// Apply `f` to the base char of every grapheme cluster of `text`, keeping any combining marks
+/// that follow it. Emoji sequences are left alone.
+fn map_graphemes(text: &str, mut f: impl FnMut(char) -> char, classes: Classes, out: &mut String) {
+    let mut f = |c| if classes.contains(c) { f(c) } else { c };
+    // every ASCII cluster is a single char apart from \r\n, which no style maps anyway
+    if text.is_ascii() {
+        out.extend(text.chars().map(&mut f));
+        return;
+    }
+    for g in text.graphemes(true) {
+        if is_emoji_cluster(g) {
+            out.push_str(g);
+            continue;
+        }
+        let mut chars = g.chars();
+        out.extend(chars.next().map(&mut f));
+        out.push_str(chars.as_str());
+    }
+}
+
+/// Offset `c` from `base` and add it to `start`. `c` must already be known to be in range.
+fn offset_char(c: char, base: char, start: u32) -> char {
+    char::from_u32(start + (c as u32 - base as u32)).unwrap()
+}
+
+/// How to write digits whatever the style does with letters, for `--digits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Digits {
+    /// Plain ASCII digits, left out of the style's [`Classes`].
+    Ascii,
+    Fullwidth,
+    /// `⓪` through `⑨`.
+    Circled,
+    /// Keycap emoji, e.g. `1️⃣`.
+    Keycap,
+}
+
+impl Digits {
+    pub const NAMES: [&'static str; 4] = ["ascii", "fullwidth", "circled", "keycap"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ascii" => Some(Digits::Ascii),
+            "fullwidth" => Some(Digits::Fullwidth),
+            "circled" => Some(Digits::Circled),
+            "keycap" => Some(Digits::Keycap),
+            _ => None,
+        }
+    }
+
+    /// Write the ASCII digits in `text` this way. Digits that are already part of a bigger
+    /// grapheme, like an existing keycap, are left alone. This has to run before the style, which
+    /// should then leave digits out of its classes so that [`Digits::Ascii`] stays ASCII.
+    pub fn apply(self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() * 3);
+        for g in text.graphemes(true) {
+            let digit = match g.as_bytes() {
+                &[b @ b'0'..=b'9'] => b as char,
+                _ => {
+                    out.push_str(g);
+                    continue;
+                }
+            };
+            match self {
+                Digits::Ascii => out.push(digit),
+                Digits::Fullwidth => out.push(fw_char(digit)),
+                Digits::Circled => out.push(circled_char(digit)),
+                Digits::Keycap => {
+                    out.push(digit);
+                    out.push_str("\u{FE0F}\u{20E3}");
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Replace ASCII punctuation with CJK punctuation, for `--cjk-punct`. Anything without a CJK
+/// equivalent is left for the style to convert. The result is no longer plain ASCII, so this has
+/// to run before the style.
+pub fn cjk_punct(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut in_double = false;
+    let mut in_single = false;
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        let between =
+            |f: fn(&char) -> bool| prev.is_some_and(|p| f(&p)) && next.is_some_and(|n| f(&n));
+        let mapped = match c {
+            // leave decimal points and thousands separators alone
+            '.' | ',' if between(char::is_ascii_digit) => c,
+            '.' => '。',
+            ',' => '、',
+            '"' => {
+                in_double = !in_double;
+                if in_double {
+                    '「'
+                } else {
+                    '」'
+                }
+            }
+            // an apostrophe inside a word like "don't" isn't a quote
+            '\'' if between(|c| c.is_alphanumeric()) => c,
+            '\'' => {
+                in_single = !in_single;
+                if in_single {
+                    '『'
+                } else {
+                    '』'
+                }
+            }
+            '[' => '【',
+            ']' => '】',
+            '<' => '〈',
+            '>' => '〉',
+            '~' => '〜',
+            _ => c,
+        };
+        out.push(mapped);
+    }
+    out
+}
+
+fn fullwidth(text: &str, classes: Classes, out: &mut String) {
+    let fw_char = |c| if classes.contains(c) { fw_char(c) } else { c };
+    if text.is_ascii() {
+        out.extend(text.chars().map(fw_char));
+        return;
+    }
+    let kana = |c| kana::to_fullwidth(c).filter(|_| classes.contains(c));
+    for g in text.graphemes(true) {
+        if is_emoji_cluster(g) {
+            out.push_str(g);
+            continue;
+        }
+        let mut chars = g.chars().peekable();
+        let Some(c) = chars.next() else {
+            continue;
+        };
+        match kana(c) {
+            // the halfwidth sound marks are grapheme extenders, so they're in the same cluster
+            Some(kana) => match chars.peek().and_then(|&mark| kana::compose(kana, mark)) {
+                Some(composed) => {
+                    chars.next();
+                    out.push(composed);
+                }
+                None => out.push(kana),
+            },
+            None => out.push(fw_char(c)),
+        }
+        // marks that couldn't be composed still get their fullwidth forms
+        out.extend(chars.map(|m| kana(m).unwrap_or(m)));
+    }
+}
+
+pub fn fw_char(c: char) -> char {
+    match c {
+        ' ' => WIDE_SPACE,
+        '!'..='~' => char::from_u32((c as u32) + FULLWIDTH_OFFSET).unwrap(),
+        _ => c,
+    }
+}
+
+/// How many levels of leet there are.
+const LEET_LEVELS: u8 = 3;
+
+/// The leet replacements for lowercase letters, and the level each one starts at. Uppercase
+/// letters get the same ones. Every replacement is used once so that decoding is unambiguous.
+const LEET: [(char, char, u8); 12] = [
+    ('a', '4', 1),
+    ('e', '3', 1),
+    ('i', '1', 1),
+    ('o', '0', 1),
+    ('s', '5', 2),
+    ('t', '7', 2),
+    ('b', '8', 2),
+    ('g', '9', 2),
+    ('z', '2', 3),
+    ('c', '(', 3),
+    ('l', '|', 3),
+    ('h', '#', 3),
+];
+
+fn leet_char<const LEVEL: u8>(c: char) -> char {
+    let lower = c.to_ascii_lowercase();
+    LEET.iter()
+        .find(|&&(letter, _, level)| letter == lower && level <= LEVEL)
+        .map_or(c, |&(_, leet, _)| leet)
+}
+
+/// The char mapping for leet at `level`, which is one of 1 through [`LEET_LEVELS`].
+fn leet_fn(level: u8) -> fn(char) -> char {
+    match level {
+        1 => leet_char::<1>,
+        2 => leet_char::<2>,
+        _ => leet_char::<3>,
+    }
+}
+
+/// Replace letters with leet speak at `level` before converting with another style, for
+/// `--leet`.
+pub fn leet(text: &str, level: u8) -> String {
+    Style::Leet {
+        level: level.clamp(1, LEET_LEVELS),
+    }
+    .apply(text)
+}
+
+/// The ASCII character for each of the 64 six-dot braille patterns, in Unicode order, from North
+/// American computer braille. Letters are the capitals.
+const BRAILLE_ASCII: &[u8; 64] =
+    b" A1B'K2L@CIF/MSP\"E3H9O6R^DJG>NTQ,*5<-U8V.%[$+X!&;:4\\0Z7(_?W]#Y)=";
+
+/// The first braille pattern, the blank one.
+const BRAILLE_BASE: u32 = 0x2800;
+
+/// Dot 7, the lower left one of eight-dot braille, which marks capitals.
+const BRAILLE_CAPITAL: u32 = 0x40;
+
+fn braille_char(c: char) -> char {
+    let (ascii, capital) = match c {
+        'a'..='z' => (c.to_ascii_uppercase(), 0),
+        'A'..='Z' => (c, BRAILLE_CAPITAL),
+        ' '..='_' => (c, 0),
+        _ => return c,
+    };
+    let dots = BRAILLE_ASCII
+        .iter()
+        .position(|&b| b == ascii as u8)
+        .unwrap() as u32;
+    char::from_u32(BRAILLE_BASE + dots + capital).unwrap()
+}
+
+fn circled_char(c: char) -> char {
+    match c {
+        'A'..='Z' => offset_char(c, 'A', 0x24B6),
+        'a'..='z' => offset_char(c, 'a', 0x24D0),
+        '0' => '\u{24EA}',
+        '1'..='9' => offset_char(c, '1', 0x2460),
+        _ => c,
+    }
+}
+
+fn math_bold_char(c: char) -> char {
+    match c {
+        'A'..='Z' => offset_char(c, 'A', 0x1D400),
+        'a'..='z' => offset_char(c, 'a', 0x1D41A),
+        '0'..='9' => offset_char(c, '0', 0x1D7CE),
+        _ => c,
+    }
+}
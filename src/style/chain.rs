@@ -0,0 +1,92 @@
+//! Several styles applied one after another, with text added around the result in between, like
+//! the styles that the `fw` command's config file defines.
+
+use super::Style;
+
+/// One step of a [`Chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Style(Style),
+    /// Text added around everything converted so far.
+    Wrap {
+        prefix: String,
+        suffix: String,
+    },
+}
+
+/// The styles and text to apply in turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain(Vec<Step>);
+
+impl Chain {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self(steps)
+    }
+
+    /// The built-in style that this chain is, if it's just one.
+    pub fn single(&self) -> Option<Style> {
+        match self.0[..] {
+            [Step::Style(style)] => Some(style),
+            _ => None,
+        }
+    }
+
+    /// Change every style in the chain with `f`, for options like `--seed`.
+    pub fn map(mut self, f: impl Fn(Style) -> Style) -> Self {
+        for step in &mut self.0 {
+            if let Step::Style(style) = step {
+                *style = f(*style);
+            }
+        }
+        self
+    }
+
+    /// Convert `text` with `convert` for each style in turn, adding the prefixes and suffixes in
+    /// between. `convert` is also told whether it's the first style, for options like
+    /// `--skip-converted` that only make sense on the input, and can update the style so that
+    /// its random choices carry on into the next call.
+    pub fn apply_with(
+        &mut self,
+        text: &str,
+        mut convert: impl FnMut(&mut Style, bool, &str) -> String,
+    ) -> String {
+        let mut text = text.to_owned();
+        let mut first = true;
+        for step in &mut self.0 {
+            text = match step {
+                Step::Style(style) => convert(style, std::mem::take(&mut first), &text),
+                Step::Wrap { prefix, suffix } => format!("{prefix}{text}{suffix}"),
+            };
+        }
+        text
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.clone()
+            .apply_with(text, |style, _, text| style.apply(text))
+    }
+
+    /// Undo each step in reverse with `decode` for the styles, for `--decode`.
+    pub fn decode_with(&self, text: &str, mut decode: impl FnMut(Style, &str) -> String) -> String {
+        let mut text = text.to_owned();
+        for step in self.0.iter().rev() {
+            text = match step {
+                Step::Style(style) => decode(*style, &text),
+                Step::Wrap { prefix, suffix } => {
+                    let inner = text.strip_prefix(prefix.as_str()).unwrap_or(&text);
+                    inner
+                        .strip_suffix(suffix.as_str())
+                        .unwrap_or(inner)
+                        .to_owned()
+                }
+            };
+        }
+        text
+    }
+}
+
+impl From<Style> for Chain {
+    fn from(style: Style) -> Self {
+        Self(vec![Step::Style(style)])
+    }
+}
@@ -0,0 +1,54 @@
+//! Cyrillic and Greek letters that look the same as ASCII ones, for the homoglyph style.
+
+/// Lookalikes for each ASCII letter that has any, the closest one first. Every lookalike appears
+/// only once, so that decoding is unambiguous.
+#[rustfmt::skip]
+const LOOKALIKES: [(char, &[char]); 36] = [
+    ('A', &['А', 'Α']), ('B', &['В', 'Β']), ('C', &['С', 'Ϲ']), ('E', &['Е', 'Ε']),
+    ('H', &['Н', 'Η']), ('I', &['І', 'Ι']), ('J', &['Ј']), ('K', &['К', 'Κ']),
+    ('M', &['М', 'Μ']), ('N', &['Ν']), ('O', &['О', 'Ο']), ('P', &['Р', 'Ρ']),
+    ('S', &['Ѕ']), ('T', &['Т', 'Τ']), ('X', &['Х', 'Χ']), ('Y', &['Ү', 'Υ']),
+    ('Z', &['Ζ']),
+    ('a', &['а']), ('c', &['с', 'ϲ']), ('d', &['ԁ']), ('e', &['е']), ('h', &['һ']),
+    ('i', &['і']), ('j', &['ј']), ('o', &['о', 'ο']), ('p', &['р']), ('q', &['ԛ']),
+    ('s', &['ѕ']), ('v', &['ν']), ('w', &['ԝ']), ('x', &['х']), ('y', &['у']),
+    // not quite identical, but close in most fonts
+    ('F', &['Ϝ']), ('G', &['Ԍ']), ('L', &['Ⳑ']), ('W', &['Ԝ']),
+];
+
+/// The lookalikes for `c`, or an empty slice if it has none.
+pub fn lookalikes(c: char) -> &'static [char] {
+    LOOKALIKES
+        .iter()
+        .find(|(ascii, _)| *ascii == c)
+        .map_or(&[], |(_, lookalikes)| lookalikes)
+}
+
+/// A small deterministic random number generator (splitmix64), so that the same `--seed` always
+/// picks the same lookalikes.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// The seed for an `Rng` that carries on from where this one is.
+    pub fn into_seed(self) -> u64 {
+        self.0
+    }
+
+    /// A random index less than `len`, which must not be zero.
+    pub fn below(&mut self, len: usize) -> usize {
+        (self.next() % len as u64) as usize
+    }
+}
@@ -0,0 +1,50 @@
+//! Halfwidth katakana (U+FF61 to U+FF9F) to their normal fullwidth forms.
+
+const VOICED_MARK: char = '\u{FF9E}';
+const SEMI_VOICED_MARK: char = '\u{FF9F}';
+
+/// Fullwidth forms of U+FF61 through U+FF9D, in order.
+#[rustfmt::skip]
+const FULLWIDTH: [char; 61] = [
+    '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ', 'ッ', 'ー',
+    'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
+    'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
+    'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン',
+];
+
+/// The fullwidth form of a halfwidth katakana character. The (semi-)voiced sound marks on their
+/// own become the spacing `゛` and `゜`.
+pub fn to_fullwidth(c: char) -> Option<char> {
+    match c {
+        '\u{FF61}'..='\u{FF9D}' => Some(FULLWIDTH[(c as u32 - 0xFF61) as usize]),
+        VOICED_MARK => Some('゛'),
+        SEMI_VOICED_MARK => Some('゜'),
+        _ => None,
+    }
+}
+
+/// Combine a fullwidth katakana with a following halfwidth sound mark into the precomposed
+/// character, e.g. `カ` + `ﾞ` = `ガ` and `ハ` + `ﾟ` = `パ`. Returns None if there's no such
+/// character, in which case the mark should be converted on its own.
+pub fn compose(kana: char, mark: char) -> Option<char> {
+    let offset = match mark {
+        VOICED_MARK => 1,
+        SEMI_VOICED_MARK => 2,
+        _ => return None,
+    };
+    let composed = match (kana, offset) {
+        // ka through chi and tsu through to are laid out as base, voiced, base, voiced...
+        // (small tsu sits between chi/di and tsu, which shifts the second run by one)
+        ('カ'..='チ', 1) if (kana as u32 - 'カ' as u32).is_multiple_of(2) => kana as u32 + 1,
+        ('ツ'..='ト', 1) if (kana as u32 - 'ツ' as u32).is_multiple_of(2) => kana as u32 + 1,
+        // ha through ho are base, voiced, semi-voiced
+        ('ハ'..='ホ', _) if (kana as u32 - 'ハ' as u32).is_multiple_of(3) => {
+            kana as u32 + offset
+        }
+        ('ウ', 1) => 'ヴ' as u32,
+        ('ワ', 1) => 'ヷ' as u32,
+        ('ヲ', 1) => 'ヺ' as u32,
+        _ => return None,
+    };
+    char::from_u32(composed)
+}
@@ -0,0 +1,87 @@
+//! Morse code, with configurable symbols.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::MorseSymbols;
+
+#[rustfmt::skip]
+const CODES: [(char, &str); 54] = [
+    ('a', ".-"), ('b', "-..."), ('c', "-.-."), ('d', "-.."), ('e', "."), ('f', "..-."),
+    ('g', "--."), ('h', "...."), ('i', ".."), ('j', ".---"), ('k', "-.-"), ('l', ".-.."),
+    ('m', "--"), ('n', "-."), ('o', "---"), ('p', ".--."), ('q', "--.-"), ('r', ".-."),
+    ('s', "..."), ('t', "-"), ('u', "..-"), ('v', "...-"), ('w', ".--"), ('x', "-..-"),
+    ('y', "-.--"), ('z', "--.."),
+    ('0', "-----"), ('1', ".----"), ('2', "..---"), ('3', "...--"), ('4', "....-"),
+    ('5', "....."), ('6', "-...."), ('7', "--..."), ('8', "---.."), ('9', "----."),
+    ('.', ".-.-.-"), (',', "--..--"), ('?', "..--.."), ('\'', ".----."), ('!', "-.-.--"),
+    ('/', "-..-."), ('(', "-.--."), (')', "-.--.-"), ('&', ".-..."), (':', "---..."),
+    (';', "-.-.-."), ('=', "-...-"), ('+', ".-.-."), ('-', "-....-"), ('_', "..--.-"),
+    ('"', ".-..-."), ('$', "...-..-"), ('@', ".--.-."),
+];
+
+/// Append `text` in morse code to `out`. Letters are separated by `symbols.letter`, and every
+/// space becomes `symbols.word` between two of those. Anything without a code, like emoji, is
+/// copied through as a letter of its own.
+pub fn encode(text: &str, symbols: MorseSymbols, out: &mut String) {
+    for line in text.split_inclusive('\n') {
+        let body = line
+            .strip_suffix("\r\n")
+            .or_else(|| line.strip_suffix('\n'))
+            .unwrap_or(line);
+        for (i, g) in body.graphemes(true).enumerate() {
+            if i > 0 {
+                out.push(symbols.letter);
+            }
+            if g == " " {
+                out.push(symbols.word);
+                continue;
+            }
+            let code = g.chars().next().and_then(|c| {
+                let c = c.to_ascii_lowercase();
+                CODES.iter().find(|(letter, _)| *letter == c)
+            });
+            match code {
+                Some((_, code)) if g.len() == 1 => {
+                    out.extend(
+                        code.chars()
+                            .map(|s| if s == '.' { symbols.dot } else { symbols.dash }),
+                    )
+                }
+                _ => out.push_str(g),
+            }
+        }
+        out.push_str(&line[body.len()..]);
+    }
+}
+
+/// Decode morse code written with `symbols` back to lowercase text. Letters that aren't morse
+/// code are left as they are.
+pub fn decode(text: &str, symbols: MorseSymbols) -> String {
+    let mut out = String::with_capacity(text.len() / 3);
+    for line in text.split_inclusive('\n') {
+        let body = line
+            .strip_suffix("\r\n")
+            .or_else(|| line.strip_suffix('\n'))
+            .unwrap_or(line);
+        for letter in body.split(symbols.letter) {
+            if letter.chars().eq([symbols.word]) {
+                out.push(' ');
+                continue;
+            }
+            let code: Option<String> = letter
+                .chars()
+                .map(|c| match c {
+                    _ if c == symbols.dot => Some('.'),
+                    _ if c == symbols.dash => Some('-'),
+                    _ => None,
+                })
+                .collect();
+            match code.and_then(|code| CODES.iter().find(|(_, c)| *c == code)) {
+                Some(&(c, _)) => out.push(c),
+                None => out.push_str(letter),
+            }
+        }
+        out.push_str(&line[body.len()..]);
+    }
+    out
+}
@@ -0,0 +1,55 @@
+//! `fw styles`, showing what each style looks like.
+
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches, Command};
+use fw::style::Style;
+
+use crate::chain;
+use crate::config::Config;
+
+const SAMPLE: &str = "Hello, cate 123";
+
+pub fn command() -> Command {
+    Command::new("styles")
+        .about("List the styles, with a sample converted to each")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .env("FW_CONFIG")
+                .help("Read the styles defined in FILE instead of the usual config file"),
+        )
+        .arg(
+            Arg::new("sample")
+                .value_name("SAMPLE")
+                .help("The text to show in each style"),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let config = Config::load(args.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let sample = args
+        .get_one::<String>("sample")
+        .map_or(SAMPLE, String::as_str);
+    let names = chain::names(&config);
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    for name in names {
+        let description = match Style::from_name(name) {
+            Some(style) => style.description(),
+            None => match config.styles[name].description.as_str() {
+                "" => "From the config file",
+                description => description,
+            },
+        };
+        // one broken style in the config file shouldn't hide the rest
+        let sample = match chain::find(&config, name) {
+            Ok(chain) => chain.apply(sample),
+            Err(err) => format!("(error: {err:#})"),
+        };
+        println!("{name:<width$}  {description}");
+        println!("{:<width$}  {sample}", "");
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! `fw table`, converting delimiter-separated input into an aligned table.
+
+use std::fs;
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgMatches, Command};
+use fw::layout::{self, Align};
+use fw::style;
+
+use crate::{cli, clipboard, input};
+
+pub fn command() -> Command {
+    clipboard::add_args(Command::new("table"))
+        .about("Convert delimiter-separated input into an aligned table")
+        .long_about(
+            "Convert delimiter-separated input into an aligned table. Each cell is converted \
+             and padded to its column's display width, so that the columns line up even in \
+             clients with proportional fonts. Fields starting with a double quote are parsed \
+             CSV-style, and may contain delimiters, newlines, and doubled \"\" quotes.",
+        )
+        .arg(cli::style_arg())
+        .arg(
+            Arg::new("delimiter")
+                .short('d')
+                .long("delimiter")
+                .value_name("CHAR")
+                .value_parser(clap::value_parser!(char))
+                .default_value("\t")
+                .hide_default_value(true)
+                .help("Field delimiter, a tab by default. Use ',' for CSV"),
+        )
+        .arg(
+            Arg::new("align")
+                .long("align")
+                .value_name("ALIGN")
+                .value_parser(["left", "center", "right"])
+                .default_value("left")
+                .help("How to align cells within their columns"),
+        )
+        .arg(
+            Arg::new("gap")
+                .long("gap")
+                .value_name("COLS")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("2")
+                .help("Blank columns between table columns"),
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Read the table from FILE instead of stdin"),
+        )
+}
+
+/// Split delimiter-separated `input` into rows of fields.
+pub fn parse(input: &str, delimiter: char) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut chars = input.chars().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => bail!("unterminated quoted field in row {}", rows.len() + 1),
+                }
+            }
+        }
+        // anything after a closing quote up to the delimiter is kept as-is, like most CSV readers
+        while let Some(&c) = chars.peek() {
+            if c == delimiter || c == '\n' {
+                break;
+            }
+            field.push(c);
+            chars.next();
+        }
+        if field.ends_with('\r') {
+            field.pop();
+        }
+        row.push(field);
+
+        match chars.next() {
+            Some('\n') => rows.push(std::mem::take(&mut row)),
+            Some(_) => (), // delimiter
+            None => {
+                // don't turn a trailing newline into an empty row
+                if row.len() > 1 || !row[0].is_empty() {
+                    rows.push(row);
+                }
+                break;
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Lay out already-converted `rows` as aligned columns separated by `gap` blank columns.
+pub fn render(rows: &[Vec<String>], align: Align, gap: usize) -> String {
+    let ncols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; ncols];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(layout::str_width(cell));
+        }
+    }
+
+    let mut out = String::new();
+    let mut line = String::new();
+    for (r, row) in rows.iter().enumerate() {
+        if r > 0 {
+            out.push('\n');
+        }
+        line.clear();
+        for (i, &width) in widths.iter().enumerate() {
+            if i > 0 {
+                layout::push_blank(&mut line, gap);
+            }
+            let cell = row.get(i).map_or("", String::as_str);
+            line.push_str(&layout::pad(cell, width, align));
+        }
+        // trailing blanks from a short last column are just noise
+        out.push_str(line.trim_end_matches([' ', style::WIDE_SPACE]));
+    }
+    out
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let input = match args.get_one::<String>("file") {
+        Some(path) => fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?,
+        None => input::read_stdin(false)?,
+    };
+    let style = cli::style_from_args(args);
+    let delimiter = *args.get_one::<char>("delimiter").unwrap();
+    let align = Align::from_name(args.get_one::<String>("align").unwrap()).unwrap();
+    let gap = *args.get_one::<usize>("gap").unwrap();
+
+    let rows: Vec<Vec<String>> = parse(&input, delimiter)?
+        .into_iter()
+        .map(|row| {
+            // cells are single-line in the output, so flatten quoted newlines
+            row.iter()
+                .map(|cell| style.apply(&cell.replace('\n', " ")))
+                .collect()
+        })
+        .collect();
+    let text = render(&rows, align, gap);
+    println!("{text}");
+    clipboard::copy(args, &text)
+}
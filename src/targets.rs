@@ -0,0 +1,81 @@
+//! `fw targets`, listing the formats currently offered on the clipboard.
+//!
+//! This is for debugging paste problems, so it talks to the display server directly instead of
+//! going through arboard, which only ever asks for text.
+
+use std::process::Command as Process;
+
+use anyhow::{bail, Context};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::env_is_nonempty;
+use crate::selection::Reader;
+
+pub fn command() -> Command {
+    Command::new("targets")
+        .about("List the formats currently offered on the clipboard")
+        .long_about(
+            "List the formats currently offered on the clipboard, to debug applications that \
+             fail to paste. On X11 these are the selection owner's TARGETS, on Wayland the MIME \
+             types reported by wl-paste.",
+        )
+        .arg(
+            Arg::new("primary")
+                .long("primary")
+                .action(ArgAction::SetTrue)
+                .help("Inspect the primary selection instead of the clipboard"),
+        )
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let primary = args.get_flag("primary");
+    if env_is_nonempty("WAYLAND_DISPLAY") {
+        wayland_targets(primary)
+    } else if env_is_nonempty("DISPLAY") {
+        x11_targets(primary)
+    } else {
+        bail!("no X11 or Wayland display found");
+    }
+}
+
+fn wayland_targets(primary: bool) -> anyhow::Result<()> {
+    let mut cmd = Process::new("wl-paste");
+    cmd.arg("--list-types");
+    if primary {
+        cmd.arg("--primary");
+    }
+    let output = cmd.output().context("failed to run wl-paste")?;
+    if !output.status.success() {
+        // wl-paste exits 1 with "Nothing is copied" on an empty selection
+        bail!(
+            "wl-paste failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    Ok(())
+}
+
+fn x11_targets(primary: bool) -> anyhow::Result<()> {
+    let reader = Reader::connect()?;
+    let selection = reader.selection(primary)?;
+    let Some(owner) = reader.owner(selection)? else {
+        bail!("nothing owns the selection");
+    };
+    match reader.window_name(owner)? {
+        // fw's own holder names its window, see holder::WINDOW_NAME
+        Some(name) => println!("owner: window 0x{owner:x} ({name})"),
+        None => println!("owner: window 0x{owner:x}"),
+    }
+
+    let Some(data) = reader.convert(selection, reader.intern("TARGETS")?)? else {
+        bail!("the selection owner refused to list its targets");
+    };
+    let Some(atoms) = data.values32() else {
+        bail!("the selection owner sent a malformed target list");
+    };
+    for atom in atoms {
+        println!("{}", reader.atom_name(atom)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! `{{...}}` placeholders for `--template`, marking which parts of a message get converted.
+
+/// Split `text` at its first complete placeholder into the text before it, the text inside it,
+/// and the text after it.
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let (before, rest) = text.split_once("{{")?;
+    let (inside, after) = rest.split_once("}}")?;
+    Some((before, inside, after))
+}
+
+/// Convert the parts of `text` inside `{{...}}` placeholders with `convert`, or if `invert` is
+/// set, the parts outside of them. The braces are removed either way. A `{{` without a matching
+/// `}}` is just text.
+pub fn apply(text: &str, invert: bool, mut convert: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut push = |part: &str, converted: bool| {
+        if converted && !part.is_empty() {
+            out.push_str(&convert(part));
+        } else {
+            out.push_str(part);
+        }
+    };
+    let mut rest = text;
+    while let Some((before, inside, after)) = next_placeholder(rest) {
+        push(before, invert);
+        push(inside, !invert);
+        rest = after;
+    }
+    push(rest, invert);
+    out
+}
@@ -0,0 +1,138 @@
+//! `fw tui`, an interactive live preview of every style.
+
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use fw::style::Style;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::style::{Modifier, Style as TermStyle};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::clipboard;
+
+pub fn command() -> Command {
+    clipboard::add_args(Command::new("tui"))
+        .about("Type text and preview every style live, then copy one")
+        .long_about(
+            "Type text and preview every style live. Use the up and down arrows to pick a \
+             style, Enter to print and copy the result, and Esc to quit without copying.",
+        )
+        .arg(
+            Arg::new("text")
+                .action(ArgAction::Append)
+                .help("Initial text. Arguments will be joined with spaces."),
+        )
+}
+
+struct App {
+    input: String,
+    selected: ListState,
+}
+
+impl App {
+    fn selected_style(&self) -> Style {
+        Style::ALL[self.selected.selected().unwrap_or(0)]
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [input_area, styles_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let input = Paragraph::new(self.input.as_str()).block(Block::bordered().title("Text"));
+        frame.render_widget(input, input_area);
+        // the cursor is always at the end, we don't support moving it around
+        let cursor_x = input_area.x + 1 + self.input.chars().count() as u16;
+        frame.set_cursor_position(Position::new(
+            cursor_x.min(input_area.right().saturating_sub(2)),
+            input_area.y + 1,
+        ));
+
+        let name_width = Style::ALL.iter().map(|s| s.name().len()).max().unwrap_or(0);
+        let items: Vec<ListItem> = Style::ALL
+            .iter()
+            .map(|style| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:name_width$}  ", style.name()),
+                        TermStyle::new().add_modifier(Modifier::DIM),
+                    ),
+                    // previews are single-line, newlines can only come from pasting
+                    Span::raw(style.apply(&self.input).replace('\n', " ")),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title("Styles"))
+            .highlight_style(TermStyle::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, styles_area, &mut self.selected);
+
+        let help = Paragraph::new("↑/↓ pick style  Enter copy  Esc quit")
+            .style(TermStyle::new().add_modifier(Modifier::DIM));
+        frame.render_widget(help, help_area);
+    }
+
+    /// Run the event loop. Returns the converted text to copy, or None if the user quit.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<Option<String>> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("failed to draw")?;
+
+            let Event::Key(key) = event::read().context("failed to read terminal event")? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(None)
+                }
+                KeyCode::Enter => return Ok(Some(self.selected_style().apply(&self.input))),
+                KeyCode::Up => self.selected.select_previous(),
+                // ListState only clamps at render time, stop here so Up works right away
+                KeyCode::Down if self.selected.selected() < Some(Style::ALL.len() - 1) => {
+                    self.selected.select_next()
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.input.clear();
+                }
+                KeyCode::Char(c) => self.input.push(c),
+                _ => (),
+            }
+        }
+    }
+}
+
+pub fn run(args: &ArgMatches) -> anyhow::Result<()> {
+    let input = match args.get_many::<String>("text") {
+        Some(words) => words.map(String::as_str).collect::<Vec<_>>().join(" "),
+        None => String::new(),
+    };
+    let mut app = App {
+        input,
+        selected: ListState::default().with_selected(Some(0)),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    // only touch the clipboard once the terminal is back to normal, so that errors are readable
+    if let Some(text) = result? {
+        println!("{text}");
+        clipboard::copy(args, &text)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Finding URLs and email addresses, so that `--keep-urls` can leave them clickable.
+//!
+//! This is a small scanner rather than a full URL parser. Anything that looks like a link once
+//! surrounding brackets, quotes, and sentence punctuation are trimmed off counts.
+
+use std::ops::Range;
+
+fn is_scheme(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn is_url(s: &str) -> bool {
+    if let Some((scheme, rest)) = s.split_once("://") {
+        return is_scheme(scheme) && !rest.is_empty();
+    }
+    let lower = s.to_ascii_lowercase();
+    ["www.", "mailto:"]
+        .iter()
+        .any(|prefix| lower.len() > prefix.len() && lower.starts_with(prefix))
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    let Some((_, tld)) = domain.rsplit_once('.') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-'))
+        && !domain.starts_with('.')
+        && !domain.contains("..")
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'))
+        && tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Find the link in a whitespace-separated `word`, if there is one.
+fn link_in(word: &str) -> Option<Range<usize>> {
+    let start = word.len() - word.trim_start_matches(['(', '<', '[', '"', '\'']).len();
+    let mut end = word.len();
+    loop {
+        let link = &word[start..end];
+        let trimmed = link.trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'', '>', ']']);
+        // keep a closing paren that belongs to the link, like in wikipedia URLs
+        let unbalanced = trimmed.matches(')').count() > trimmed.matches('(').count();
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(t) if unbalanced => t,
+            _ => trimmed,
+        };
+        if trimmed.len() == link.len() {
+            break;
+        }
+        end = start + trimmed.len();
+    }
+    let link = &word[start..end];
+    (is_url(link) || is_email(link)).then_some(start..end)
+}
+
+/// Byte ranges of every URL and email address in `text`.
+pub fn find(text: &str) -> Vec<Range<usize>> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end_matches(char::is_whitespace);
+        if let Some(link) = link_in(trimmed) {
+            links.push(offset + link.start..offset + link.end);
+        }
+        offset += word.len();
+    }
+    links
+}
+
+/// Convert everything in `text` except URLs and email addresses with `convert`.
+pub fn apply(text: &str, mut convert: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut last = 0;
+    for link in find(text) {
+        if link.start > last {
+            out.push_str(&convert(&text[last..link.start]));
+        }
+        out.push_str(&text[link.clone()]);
+        last = link.end;
+    }
+    if last < text.len() {
+        out.push_str(&convert(&text[last..]));
+    }
+    out
+}
@@ -0,0 +1,28 @@
+//! JavaScript bindings for the conversion core, so that a browser can use the same tables.
+//!
+//! Build with `wasm-pack build --target web --no-default-features --features wasm`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::style::Style;
+
+/// Convert `text` to fullwidth.
+#[wasm_bindgen(js_name = toFullwidth)]
+pub fn to_fullwidth(text: &str) -> String {
+    Style::Fullwidth.apply(text)
+}
+
+/// Convert fullwidth text back to ASCII.
+#[wasm_bindgen(js_name = fromFullwidth)]
+pub fn from_fullwidth(text: &str) -> String {
+    Style::Fullwidth.decode(text)
+}
+
+/// Convert `text` to the style called `style`, like `fw --style`.
+#[wasm_bindgen(js_name = applyStyle)]
+pub fn apply_style(text: &str, style: &str) -> Result<String, JsError> {
+    match Style::from_name(style) {
+        Some(style) => Ok(style.apply(text)),
+        None => Err(JsError::new(&format!("unknown style '{style}'"))),
+    }
+}
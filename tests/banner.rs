@@ -0,0 +1,20 @@
+//! `fw banner`.
+
+use std::process::Command;
+
+fn banner(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .args(["banner", "--no-clipboard"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn dots_pack_the_tiny_font_into_braille() {
+    // two by two cells per letter, with the last blank column trimmed
+    assert_eq!(banner(&["--dots", "HI"]), "⡧⡇⢹⠁\n⠁⠁⠉⠁\n");
+    assert_eq!(banner(&["--dots", "I\nI"]), "⢹⠁\n⠉⠁\n⢹⠁\n⠉⠁\n");
+}
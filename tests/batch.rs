@@ -0,0 +1,34 @@
+//! `fw batch` converts a whole file of messages in one run.
+
+use std::fs;
+use std::process::Command;
+
+fn batch(input: &str, args: &[&str]) -> String {
+    let path = std::env::temp_dir().join(format!("fw-test-{}-batch.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("batch")
+        .arg("--input")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn batch_writes_lines_and_mappings() {
+    let input = "hi\n\nhi\na\tb\n";
+    assert_eq!(batch(input, &[]), "ｈｉ\n\nｈｉ\nａ\tｂ\n");
+    // blank and repeated messages are left out of mappings
+    assert_eq!(
+        batch(input, &["--format", "tsv"]),
+        "hi\tｈｉ\na\\tb\tａ\\tｂ\n"
+    );
+    assert_eq!(
+        batch(input, &["--format", "json", "--style", "spaced"]),
+        "{\n  \"hi\": \"h i\",\n  \"a\\tb\": \"a \\t b\"\n}\n"
+    );
+}
@@ -0,0 +1,15 @@
+//! `--bidi isolate` on converted text.
+
+use fw::bidi;
+use fw::style::Style;
+
+#[test]
+fn isolates_right_to_left_runs() {
+    let text = Style::Fullwidth.apply("hi שלום עולם 42 ok\nשלום hi");
+    assert_eq!(
+        bidi::isolate(&text),
+        "ｈｉ　\u{2068}שלום　עולם　４２\u{2069}　ｏｋ\nשלום　ｈｉ"
+    );
+    assert!(!bidi::has_rtl("cate"));
+    assert_eq!(bidi::isolate("ｃａｔｅ"), "ｃａｔｅ");
+}
@@ -0,0 +1,57 @@
+//! The C bindings, called the way C would call them.
+#![cfg(feature = "capi")]
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use fw::capi::*;
+
+/// Call `f` with an out pointer and take the string it returns.
+fn call(f: impl FnOnce(*mut *mut c_char) -> i32) -> Result<String, i32> {
+    let mut out = ptr::null_mut();
+    match f(&mut out) {
+        FW_OK => unsafe {
+            let s = CStr::from_ptr(out).to_str().unwrap().to_owned();
+            fw_free(out);
+            Ok(s)
+        },
+        err => {
+            assert!(out.is_null());
+            Err(err)
+        }
+    }
+}
+
+#[test]
+fn converts_and_decodes() {
+    let text = c"cate 42".as_ptr();
+    let wide = call(|out| unsafe { fw_to_fullwidth(text, out) }).unwrap();
+    assert_eq!(wide, "ｃａｔｅ　４２");
+    let wide = std::ffi::CString::new(wide).unwrap();
+    assert_eq!(
+        call(|out| unsafe { fw_from_fullwidth(wide.as_ptr(), out) }),
+        Ok("cate 42".to_owned())
+    );
+    assert_eq!(
+        call(|out| unsafe { fw_apply_style(text, c"circled".as_ptr(), 0, out) }),
+        Ok("ⓒⓐⓣⓔ ④②".to_owned())
+    );
+}
+
+#[test]
+fn reports_errors() {
+    let text = c"cate".as_ptr();
+    assert_eq!(
+        call(|out| unsafe { fw_apply_style(text, c"nope".as_ptr(), 0, out) }),
+        Err(FW_ERR_STYLE)
+    );
+    assert_eq!(
+        call(|out| unsafe { fw_to_fullwidth(ptr::null(), out) }),
+        Err(FW_ERR_NULL)
+    );
+    assert_eq!(
+        call(|out| unsafe { fw_to_fullwidth(c"\xff".as_ptr(), out) }),
+        Err(FW_ERR_UTF8)
+    );
+    unsafe { fw_free(ptr::null_mut()) };
+}
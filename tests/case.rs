@@ -0,0 +1,23 @@
+//! `--case`, which has to handle more than ASCII.
+
+use fw::case::Case;
+
+#[test]
+fn title_case_is_unicode_aware() {
+    assert_eq!(
+        Case::Title.apply("héllo wORLD ΟΔΟΣ it's"),
+        "Héllo World Οδος It's"
+    );
+    assert_eq!(Case::Upper.apply("straße"), "STRASSE");
+    // the SGR code's final 'm' stays lower case
+    assert_eq!(Case::Upper.apply("\x1b[1mbold"), "\x1b[1mBOLD");
+}
+
+#[test]
+fn random_case_follows_the_seed() {
+    let text = "the quick brown fox";
+    let random = |seed| Case::Random { seed }.apply(text);
+    assert_eq!(random(1), random(1));
+    assert_ne!(random(1), random(2));
+    assert_eq!(random(1).to_lowercase(), text);
+}
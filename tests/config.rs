@@ -0,0 +1,107 @@
+//! Things the config file can define, through the `fw` command.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Write `config` to a file of its own for the test called `name`.
+fn config_file(name: &str, config: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fw-test-{}-{name}.toml", std::process::id()));
+    fs::write(&path, config).unwrap();
+    path
+}
+
+fn fw(config: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("--config")
+        .arg(config)
+        .arg("--no-clipboard")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn composite_styles_chain() {
+    let config = config_file(
+        "chain",
+        r#"
+        [styles.meme]
+        chain = ["fullwidth", "spaced"]
+        suffix = " ✨"
+
+        [styles.loud]
+        chain = ["meme", "reversed"]
+        prefix = "!"
+        "#,
+    );
+    let output = fw(&config, &["-s", "meme", "cate"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ｃ ａ ｔ ｅ ✨\n");
+    let output = fw(&config, &["-s", "loud", "ab"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "!✨ ｂ ａ\n");
+    let output = fw(&config, &["-s", "loud", "--decode", "!✨ ｂ ａ"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ab\n");
+    fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn composite_style_cycles_are_errors() {
+    let config = config_file(
+        "cycle",
+        r#"
+        [styles.a]
+        chain = ["fullwidth", "b"]
+        [styles.b]
+        chain = ["a"]
+        "#,
+    );
+    let output = fw(&config, &["-s", "b", "x"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("includes itself: b -> a -> b"));
+    fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn profiles_inherit_and_yield_to_the_command_line() {
+    let config = config_file(
+        "profile",
+        r#"
+        [profiles.chat]
+        style = "circled"
+        quote = true
+
+        [profiles.irc]
+        inherits = "chat"
+        max-chars = 5
+        quote = false
+        "#,
+    );
+    let output = fw(&config, &["-p", "chat", "cate"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "＞ ⓒⓐⓣⓔ\n");
+    let output = fw(&config, &["-p", "irc", "catecate"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ⓒⓐⓣⓔ…\n");
+    let output = fw(&config, &["-p", "irc", "-s", "fullwidth", "cate"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ｃａｔｅ\n");
+    fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn protect_list_yields_to_protect_file() {
+    let config = config_file("protect", "protect = [\"fw\", \"@*\"]\n");
+    let output = fw(&config, &["ping @Alice about FW"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "ｐｉｎｇ　@Alice　ａｂｏｕｔ　FW\n"
+    );
+    let file = config_file("protect-file", "about fw\n");
+    let output = fw(
+        &config,
+        &["--protect-file", file.to_str().unwrap(), "@al about fw"],
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "＠ａｌ　about fw\n"
+    );
+    fs::remove_file(config).unwrap();
+    fs::remove_file(file).unwrap();
+}
@@ -0,0 +1,57 @@
+//! The whole pipeline through `fw::convert`, like the fuzz target does.
+
+use fw::case::Case;
+use fw::convert::{convert, convert_bytes, Options};
+use fw::layout::{Align, BoxChars, Limit, Tabs, Truncate};
+use fw::protect::Protect;
+use fw::style::{Digits, Style};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn any_bytes_convert_to_utf8(input in any::<Vec<u8>>(), cols in 0usize..16) {
+        for style in Style::ALL {
+            let options = Options {
+                style: style.into(),
+                emoji: true,
+                strip_invisible: true,
+                bidi_isolate: true,
+                case: Some(Case::Random { seed: cols as u64 }),
+                digits: Some(Digits::Keycap),
+                keep_urls: true,
+                protect: Protect::parse("fw\n*.rs"),
+                template: Some(false),
+                expand_tabs: Some(Tabs::Stops(cols)),
+                wrap: Some(cols),
+                number: true,
+                quote: true,
+                pad: Some((cols, Align::Right)),
+                draw_box: BoxChars::from_spec("double"),
+                truncate: Some((Limit::Chars(cols * 4), Truncate::Middle, "…".to_owned())),
+                ..Options::default()
+            };
+            prop_assert!(String::from_utf8(convert_bytes(&input, &options)).is_ok());
+        }
+    }
+}
+
+#[test]
+fn invalid_utf8_is_replaced() {
+    let options = Options::default();
+    assert_eq!(
+        convert_bytes(b"a\xffb", &options),
+        "ａ\u{FFFD}ｂ".as_bytes()
+    );
+}
+
+#[test]
+fn protected_words_are_left_alone() {
+    let options = Options {
+        protect: Protect::parse("# names\nfw\nhello world\n*.rs\n"),
+        ..Options::default()
+    };
+    assert_eq!(
+        convert("Hello World, fw wrote fwd (main.rs).", &options),
+        "Hello World，　fw　ｗｒｏｔｅ　ｆｗｄ　（main.rs）．"
+    );
+}
@@ -0,0 +1,33 @@
+//! `--each` prints every text, and `--copy-index` picks which of them would be copied.
+
+use std::process::{Command, Output};
+
+fn dry_run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fw"))
+        .args(["--dry-run", "--each"])
+        .args(args)
+        // a display that's never connected to, just so that there's somewhere to copy to
+        .env("DISPLAY", "fw.invalid:255")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn copy_index_picks_the_copied_text() {
+    for (index, bytes) in [("1", 3), ("last", 3), ("all", 11)] {
+        let output = dry_run(&["--copy-index", index, "a", "b", "c"]);
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ａ\nｂ\nｃ\n");
+        assert!(
+            String::from_utf8_lossy(&output.stderr)
+                .contains(&format!("would copy {bytes} bytes of text")),
+            "{output:?}"
+        );
+    }
+
+    let output = dry_run(&["--copy-index", "4", "a", "b", "c"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("--copy-index 4 is past the last of the 3 texts"));
+}
@@ -0,0 +1,181 @@
+//! Styles and layout on input whose grapheme clusters are more than one char.
+
+use fw::invisible;
+use fw::layout::{self, Align, Limit, Truncate};
+use fw::style::{Chaos, Classes, Digits, Style};
+
+const FAMILY: &str = "👨\u{200D}👩\u{200D}👧";
+const FLAG: &str = "🇯🇵";
+const KEYCAP_ONE: &str = "1\u{FE0F}\u{20E3}";
+const KEYCAP_HASH: &str = "#\u{FE0F}\u{20E3}";
+const WAVE_MEDIUM: &str = "👋🏽";
+const E_ACUTE: &str = "e\u{301}";
+
+#[test]
+fn fullwidth_keeps_emoji_sequences() {
+    for emoji in [FAMILY, FLAG, KEYCAP_ONE, KEYCAP_HASH, WAVE_MEDIUM] {
+        let input = format!("a{emoji}b");
+        assert_eq!(Style::Fullwidth.apply(&input), format!("ａ{emoji}ｂ"));
+    }
+}
+
+#[test]
+fn combining_marks_stay_on_their_base() {
+    assert_eq!(Style::Fullwidth.apply(E_ACUTE), "ｅ\u{301}");
+    assert_eq!(Style::Circled.apply(E_ACUTE), "ⓔ\u{301}");
+    assert_eq!(Style::MathBold.apply(E_ACUTE), "𝐞\u{301}");
+}
+
+#[test]
+fn circled_and_math_bold_keep_keycaps() {
+    assert_eq!(Style::Circled.apply(KEYCAP_ONE), KEYCAP_ONE);
+    assert_eq!(Style::MathBold.apply(KEYCAP_ONE), KEYCAP_ONE);
+    assert_eq!(Style::Circled.apply("1"), "①");
+}
+
+#[test]
+fn digits_make_keycaps_but_keep_existing_ones() {
+    let text = format!("{KEYCAP_ONE}2{E_ACUTE}");
+    assert_eq!(
+        Digits::Keycap.apply(&text),
+        format!("{KEYCAP_ONE}2\u{FE0F}\u{20E3}{E_ACUTE}")
+    );
+    assert_eq!(
+        Digits::Circled.apply(&text),
+        format!("{KEYCAP_ONE}②{E_ACUTE}")
+    );
+}
+
+#[test]
+fn halfwidth_sound_marks_compose() {
+    assert_eq!(Style::Fullwidth.apply("ｶﾞｷﾞ"), "ガギ");
+    assert_eq!(Style::Fullwidth.apply("ﾊﾟ"), "パ");
+    // nothing to compose with, but the mark is still converted
+    assert_eq!(Style::Fullwidth.apply("aﾞ"), "ａ゛");
+}
+
+#[test]
+fn spaced_treats_clusters_atomically() {
+    let input = format!("{FAMILY}{FLAG}{E_ACUTE}");
+    assert_eq!(
+        Style::Spaced.apply(&input),
+        format!("{FAMILY} {FLAG} {E_ACUTE}")
+    );
+    assert_eq!(Style::Spaced.apply("ab\r\ncd"), "a b\r\nc d");
+}
+
+#[test]
+fn reversed_treats_clusters_atomically() {
+    let input = format!("{E_ACUTE}{FLAG}{WAVE_MEDIUM}x");
+    assert_eq!(
+        Style::Reversed.apply(&input),
+        format!("x{WAVE_MEDIUM}{FLAG}{E_ACUTE}")
+    );
+    assert_eq!(Style::Reversed.apply("ab\r\ncd"), "ba\r\ndc");
+    assert_eq!(Style::Reversed.apply(FAMILY), FAMILY);
+}
+
+#[test]
+fn widths_count_clusters() {
+    assert_eq!(layout::str_width(FAMILY), 2);
+    assert_eq!(layout::str_width(FLAG), 2);
+    assert_eq!(layout::str_width(E_ACUTE), 1);
+    assert_eq!(layout::str_width("\r\n"), 0);
+}
+
+#[test]
+fn wrap_never_splits_a_cluster() {
+    let input = format!("{FAMILY}{FAMILY}{FAMILY}");
+    assert_eq!(
+        layout::wrap(&input, 4),
+        format!("{FAMILY}{FAMILY}\n{FAMILY}")
+    );
+    let input = E_ACUTE.repeat(3);
+    assert_eq!(
+        layout::wrap(&input, 2),
+        format!("{E_ACUTE}{E_ACUTE}\n{E_ACUTE}")
+    );
+}
+
+#[test]
+fn pad_truncates_between_clusters() {
+    let input = format!("{FLAG}{FLAG}");
+    assert_eq!(layout::pad(&input, 3, Align::Left), format!("{FLAG} "));
+    let input = format!("a{E_ACUTE}b");
+    assert_eq!(layout::pad(&input, 2, Align::Left), format!("a{E_ACUTE}"));
+}
+
+#[test]
+fn truncate_counts_clusters() {
+    let input = format!("{FAMILY}{E_ACUTE}{FLAG}ab");
+    assert_eq!(
+        layout::truncate(&input, Limit::Chars(3), Truncate::End, "…"),
+        format!("{FAMILY}{E_ACUTE}…")
+    );
+    assert_eq!(
+        layout::truncate(&input, Limit::Width(6), Truncate::Start, "…"),
+        format!("…{E_ACUTE}{FLAG}ab")
+    );
+    assert_eq!(
+        layout::truncate(&input, Limit::Width(4), Truncate::Middle, "．"),
+        "．b"
+    );
+    assert_eq!(
+        layout::truncate(&input, Limit::Chars(5), Truncate::End, "…"),
+        input
+    );
+    // the reset after the cut is kept
+    assert_eq!(
+        layout::truncate("\x1b[1mbold\x1b[0m", Limit::Chars(3), Truncate::End, "…"),
+        "\x1b[1mbo…\x1b[0m"
+    );
+}
+
+#[test]
+fn truncate_cuts_an_ellipsis_that_doesnt_fit() {
+    assert_eq!(
+        layout::truncate("hello", Limit::Chars(0), Truncate::End, "…"),
+        ""
+    );
+    assert_eq!(
+        layout::truncate("hello", Limit::Chars(1), Truncate::End, "..."),
+        "."
+    );
+    assert_eq!(
+        layout::truncate("hello", Limit::Width(4), Truncate::Middle, "．．．"),
+        "．．"
+    );
+    // the column the cut ellipsis leaves over still gets some of the text
+    assert_eq!(
+        layout::truncate("hello", Limit::Width(3), Truncate::Start, "．．"),
+        "．o"
+    );
+}
+
+#[test]
+fn strip_invisible_keeps_emoji_joiners() {
+    let input = format!("\u{FEFF}ca\u{200B}te {FAMILY}\u{200F} a\u{200D}b");
+    let (text, removed) = invisible::strip(&input);
+    assert_eq!(text, format!("cate {FAMILY} ab"));
+    let removed: Vec<(char, usize)> = removed.iter().map(|(c, _, n)| (c, n)).collect();
+    assert_eq!(
+        removed,
+        [
+            ('\u{FEFF}', 1),
+            ('\u{200B}', 1),
+            ('\u{200D}', 1),
+            ('\u{200F}', 1)
+        ]
+    );
+}
+
+#[test]
+fn chaos_is_seeded_and_keeps_clusters() {
+    let styles = vec![Style::Fullwidth, Style::Circled, Style::MathBold];
+    let input = format!("abc{FAMILY}{E_ACUTE}xyz");
+    let once = Chaos::new(styles.clone(), 7).apply(&input, Classes::ALL);
+    let again = Chaos::new(styles, 7).apply(&input, Classes::ALL);
+    assert_eq!(once, again);
+    assert!(once.contains(FAMILY), "{once}");
+    assert!(!once.contains("abc"), "{once}");
+}
@@ -0,0 +1,33 @@
+//! `fw history` lists what `--history` recorded, and copies entries by their number.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn fw(data_home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fw"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_home)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn copy_counts_the_whole_history() {
+    let data_home = std::env::temp_dir().join(format!("fw-test-{}-history", std::process::id()));
+    for text in ["one", "two", "three"] {
+        let output = fw(&data_home, &["--no-clipboard", "--history", text]);
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    // entry 3 is the oldest, even though --limit and the search leave it out of the list
+    for args in [&["--limit", "1"][..], &["two"]] {
+        let output = fw(
+            &data_home,
+            &[&["history", "--no-clipboard", "--copy", "3"], args].concat(),
+        );
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "ｏｎｅ\n");
+    }
+    fs::remove_dir_all(&data_home).unwrap();
+}
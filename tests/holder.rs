@@ -0,0 +1,38 @@
+//! The background holder can be run on its own, the same way `fw` starts it.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn hold(text_len: usize, stdin: &[u8], display: Option<&str>) -> Output {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_fw"));
+    cmd.arg(format!("--__clipboard-holder={text_len}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    match display {
+        Some(display) => cmd.env("DISPLAY", display),
+        None => cmd.env_remove("DISPLAY"),
+    };
+    let mut child = cmd.spawn().unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn holder_checks_its_input() {
+    let output = hold(10, b"short", None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected 10 bytes of text"));
+
+    let output = hold(2, b"\xff\xfe", None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not valid UTF-8"));
+}
+
+#[test]
+fn holder_reports_a_missing_display() {
+    // display 255 on a made-up host is never going to answer
+    let output = hold(2, b"hi", Some("fw.invalid:255"));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed to connect to the X server"));
+}
@@ -0,0 +1,49 @@
+//! Line-oriented layout like `--quote`, `--number`, `--wrap`, and `--box`.
+
+use fw::layout::{self, BoxChars};
+
+#[test]
+fn numbers_line_up() {
+    let text: Vec<String> = (1..=10).map(|n| n.to_string()).collect();
+    let numbered = layout::number(&text.join("\n"));
+    let lines: Vec<&str> = numbered.lines().collect();
+    assert_eq!(lines[0], "　１　1");
+    assert_eq!(lines[9], "１０　10");
+}
+
+#[test]
+fn quote_skips_the_trailing_newline() {
+    assert_eq!(layout::quote("a\r\nb\n"), "＞ a\r\n＞ b\n");
+    assert_eq!(layout::quote(""), "＞ ");
+    assert_eq!(layout::number("a\n\nb"), "１　a\n２　\n３　b");
+}
+
+#[test]
+fn mixed_widths_ignore_spaces() {
+    assert!(layout::mixes_widths("a｜b"));
+    assert!(!layout::mixes_widths("ａ　ｂ "));
+    assert!(!layout::mixes_widths("a b"));
+    // an escape sequence isn't halfwidth text
+    assert!(!layout::mixes_widths("\x1b[1mａ\x1b[0m"));
+}
+
+#[test]
+fn wrap_breaks_match_the_line_endings() {
+    assert_eq!(layout::wrap("ａｂ\r\nｃｄ", 2), "ａ\r\nｂ\r\nｃ\r\nｄ");
+    assert_eq!(layout::wrap("ａｂ\nｃ\n", 2), "ａ\nｂ\nｃ\n");
+    // the space at a break is dropped, not carried onto the next line
+    assert_eq!(layout::wrap("ab cd\r\n", 3), "ab\r\ncd\r\n");
+}
+
+#[test]
+fn box_rows_match_the_line_endings() {
+    let ascii = BoxChars::from_spec("ascii").unwrap();
+    assert_eq!(
+        layout::draw_box("ａｂ\r\nｃ", &ascii),
+        "+--------+\r\n|　ａｂ　|\r\n|　ｃ　　|\r\n+--------+"
+    );
+    assert_eq!(
+        layout::draw_box("ａｂ\nｃ", &ascii),
+        "+--------+\n|　ａｂ　|\n|　ｃ　　|\n+--------+"
+    );
+}
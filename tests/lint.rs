@@ -0,0 +1,66 @@
+//! `fw lint` reports lines that won't line up, and `--fix` pads them.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn lint(input: &str, args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("lint")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn lint_reports_wide_and_mixed_lines() {
+    let output = lint("ok\na｜b\n１２３４\n", &["--columns", "6"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "line 2: mixes halfwidth and fullwidth characters\n\
+         line 3: 8 columns wide, over the limit of 6\n"
+    );
+    assert!(lint("hi\n", &["--columns", "4", "--style", "fullwidth"])
+        .status
+        .success());
+}
+
+#[test]
+fn lint_fix_pads_every_line() {
+    let output = lint(
+        "hi\nthere\n",
+        &["--columns", "8", "--style", "fullwidth", "--fix"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "ｈｉ　　\nｔｈｅｒ\n"
+    );
+}
+
+#[test]
+fn lint_fix_still_fails_on_mixed_widths() {
+    let output = lint("a｜b\nok\n", &["--columns", "6", "--fix"]);
+    assert!(!output.status.success());
+    // the padded text is printed all the same
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a｜b　\nok　　\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("line 1: mixes halfwidth and fullwidth characters"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("1 problem found"), "{stderr}");
+}
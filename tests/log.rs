@@ -0,0 +1,56 @@
+//! Errors go through `--log-format` and `--log-file`.
+
+use std::fs;
+use std::process::{Command, Output, Stdio};
+
+fn fw(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("--no-clipboard")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn errors_can_be_json() {
+    let output = fw(&["--style", "nope", "hi"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("Error: unknown style 'nope'"),
+        "{stderr}"
+    );
+
+    let output = fw(&["--log-format", "json", "--style", "nope", "hi"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with('{') && stderr.ends_with("}\n"),
+        "{stderr}"
+    );
+    assert!(stderr.contains(r#""level":"ERROR""#), "{stderr}");
+    assert!(stderr.contains("unknown style 'nope'"), "{stderr}");
+}
+
+#[test]
+fn log_file_gets_timestamped_messages() {
+    let path = std::env::temp_dir().join(format!("fw-test-{}.log", std::process::id()));
+    let child = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("--log-file")
+        .arg(&path)
+        .args(["--no-clipboard", "--style", "nope", "hi"])
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let pid = child.id();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stderr.is_empty());
+    let log = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(log.starts_with("20"), "{log}");
+    assert!(
+        log.contains(&format!(" fw[{pid}]: error: unknown style 'nope'")),
+        "{log}"
+    );
+}
@@ -0,0 +1,132 @@
+//! `decode(encode(s)) == normalize(s)` for every style.
+
+use std::process::Command;
+
+use fw::roundtrip;
+use fw::style::{Classes, MorseSymbols, Style};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn ascii_round_trips(text in "[\\x00-\\x7f]*") {
+        for style in Style::ALL {
+            prop_assert_eq!(roundtrip::check(style, &text), Ok(()));
+        }
+    }
+
+    #[test]
+    fn printable_lines_round_trip(text in "[ -~]{0,40}(\r?\n[ -~]{0,40}){0,4}") {
+        for style in Style::ALL {
+            prop_assert_eq!(roundtrip::check(style, &text), Ok(()));
+            // leet can't tell its digits from the input's, and neither it nor morse keeps case
+            if !matches!(style, Style::Leet { .. } | Style::Morse(_)) {
+                prop_assert_eq!(roundtrip::normalize(style, &text), text.clone());
+            }
+        }
+    }
+}
+
+#[test]
+fn already_converted_text_normalizes() {
+    assert_eq!(
+        roundtrip::normalize(Style::Fullwidth, "ｃａｔｅ cat"),
+        "cate cat"
+    );
+    assert_eq!(roundtrip::check(Style::Fullwidth, "ｃａｔｅ cat"), Ok(()));
+}
+
+#[test]
+fn partly_converted_text_converts_uniformly() {
+    let style = Style::Circled;
+    let mixed = "ｃａｔｅ ⓒⓐⓣⓔ cate";
+    assert_eq!(
+        style.apply_classes(&style.decode_converted(mixed, Classes::ALL), Classes::ALL),
+        style.apply("cate cate cate")
+    );
+    assert_eq!(
+        style.apply_unconverted(mixed, Classes::ALL),
+        "ｃａｔｅ ⓒⓐⓣⓔ ⓒⓐⓣⓔ"
+    );
+    // spacing out a fullwidth run would break it into halves
+    assert_eq!(
+        Style::Spaced.apply_unconverted("ab ｃｄ", Classes::ALL),
+        "a b  ｃｄ"
+    );
+}
+
+#[test]
+fn seeded_homoglyphs_round_trip() {
+    let text = "Hello, World! cate 123";
+    let style = Style::Homoglyph { seed: None };
+    assert_ne!(style.apply(text), text);
+    let seeded: Vec<String> = (0..8)
+        .map(|seed| style.with_seed(seed).apply(text))
+        .collect();
+    assert_eq!(seeded[3], style.with_seed(3).apply(text));
+    assert!(seeded.iter().any(|s| *s != seeded[0]));
+    for out in &seeded {
+        assert_eq!(style.decode(out), text);
+    }
+}
+
+#[test]
+fn leet_levels_round_trip() {
+    let text = "Hello Leet Cate, 42";
+    let levels: Vec<String> = (1..=3)
+        .map(|level| Style::Leet { level }.apply(text))
+        .collect();
+    assert_eq!(
+        levels,
+        [
+            "H3ll0 L33t C4t3, 42",
+            "H3ll0 L337 C473, 42",
+            "#3||0 |337 (473, 42"
+        ]
+    );
+    for level in 1..=3 {
+        assert_eq!(roundtrip::check(Style::Leet { level }, text), Ok(()));
+    }
+}
+
+#[test]
+fn morse_symbols_round_trip() {
+    let symbols = MorseSymbols::parse("·−|").unwrap();
+    let style = Style::Morse(symbols);
+    assert_eq!(style.apply("SOS sos"), "··· −−− ··· | ··· −−− ···");
+    assert_eq!(style.decode(&style.apply("SOS, 🐱!\n")), "sos, 🐱!\n");
+    let fullwidth = Style::Morse(MorseSymbols::FULLWIDTH);
+    assert_eq!(fullwidth.apply("e t"), "．　／　－");
+    assert!(MorseSymbols::parse("..").is_err());
+}
+
+#[test]
+fn reversed_moves_a_leading_carriage_return() {
+    assert_eq!(
+        roundtrip::normalize(Style::Reversed, "\rab\ncd"),
+        "ab\r\ncd"
+    );
+    assert_eq!(roundtrip::check(Style::Reversed, "\rab\ncd"), Ok(()));
+}
+
+#[test]
+fn roundtrip_check_command() {
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_fw"))
+            .arg("roundtrip-check")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = run(&["hello world", "a\r\nb", "  ~!@#$%^&*()  "]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "27 round trips ok\n"
+    );
+
+    let output = run(&["--style", "spaced", "x"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1 round trips ok\n"
+    );
+}
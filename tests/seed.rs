@@ -0,0 +1,124 @@
+//! A seed picks the same however the input arrives: all at once, a line at a time on
+//! stdin, or split up by `--template` placeholders, for the styles, `--case random`, and
+//! `--chaos` alike.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use fw::case::Case;
+use fw::convert::{convert, Options};
+use fw::style::Style;
+
+/// Convert `input` from stdin with `args`.
+fn fw_stdin(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("--no-clipboard")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn fw(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fw"))
+        .arg("--no-clipboard")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn homoglyph_picks_carry_on_across_lines() {
+    let args = ["-s", "homoglyph", "--seed", "5"];
+    let whole = fw(&[&args[..], &["ACEOcop\nACEOcop"]].concat());
+    let (first, second) = whole.trim_end().split_once('\n').unwrap();
+    assert_ne!(first, second);
+    for jobs in ["1", "4"] {
+        let streamed = fw_stdin(&[&args[..], &["-j", jobs]].concat(), "ACEOcop\nACEOcop\n");
+        assert_eq!(streamed, whole, "-j{jobs}");
+    }
+}
+
+#[test]
+fn homoglyph_picks_carry_on_across_placeholders() {
+    let output = fw(&[
+        "-s",
+        "homoglyph",
+        "--seed",
+        "5",
+        "--template",
+        "{{ACEOcop}} {{ACEOcop}}",
+    ]);
+    let (first, second) = output.trim_end().split_once(' ').unwrap();
+    assert_ne!(first, second);
+
+    let options = Options {
+        style: Style::Homoglyph { seed: Some(5) }.into(),
+        template: Some(false),
+        ..Options::default()
+    };
+    assert_eq!(
+        convert("{{ACEOcop}} {{ACEOcop}}", &options),
+        output.trim_end()
+    );
+}
+
+#[test]
+fn random_case_carries_on_across_lines_and_placeholders() {
+    let args = ["--case", "random", "--seed", "3"];
+    let whole = fw(&[&args[..], &["abcdefgh\nabcdefgh"]].concat());
+    for jobs in ["1", "4"] {
+        let streamed = fw_stdin(&[&args[..], &["-j", jobs]].concat(), "abcdefgh\nabcdefgh\n");
+        assert_eq!(streamed, whole, "-j{jobs}");
+    }
+
+    let output = fw(&[&args[..], &["--template", "{{abcdefgh}} {{abcdefgh}}"]].concat());
+    let (first, second) = output.trim_end().split_once(' ').unwrap();
+    assert_ne!(first, second);
+
+    let options = Options {
+        case: Some(Case::Random { seed: 3 }),
+        template: Some(false),
+        ..Options::default()
+    };
+    assert_eq!(
+        convert("{{abcdefgh}} {{abcdefgh}}", &options),
+        output.trim_end()
+    );
+}
+
+#[test]
+fn chaos_picks_carry_on_across_lines_and_placeholders() {
+    let whole = fw(&["--chaos=1", "abcdefgh\nabcdefgh"]);
+    let (first, second) = whole.trim_end().split_once('\n').unwrap();
+    assert_ne!(first, second);
+    for jobs in ["1", "4"] {
+        let streamed = fw_stdin(&["--chaos=1", "-j", jobs], "abcdefgh\nabcdefgh\n");
+        assert_eq!(streamed, whole, "-j{jobs}");
+    }
+
+    let output = fw(&["--chaos=2", "--template", "{{abcdefgh}} and {{abcdefgh}}"]);
+    let (first, second) = output.trim_end().split_once(" and ").unwrap();
+    assert_ne!(first, second);
+
+    let options = Options {
+        chaos: Some((vec![Style::Fullwidth, Style::Circled, Style::MathBold], 2)),
+        template: Some(false),
+        ..Options::default()
+    };
+    let converted = convert("{{abcdefgh}} and {{abcdefgh}}", &options);
+    let (first, second) = converted.split_once(" and ").unwrap();
+    assert_ne!(first, second);
+}
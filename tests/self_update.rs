@@ -0,0 +1,189 @@
+//! `fw self-update` installs a release from a local stand-in for the release endpoint.
+#![cfg(feature = "self-update")]
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::thread;
+
+/// Serve a release of `binary` with `sums` for its SHA256SUMS, signed with `signature` if there
+/// is one, until the test exits, and return the URL of its release info.
+fn serve_release(binary: &[u8], sums: &str, signature: Option<&str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let name = format!("fw-{}-{}", std::env::consts::ARCH, std::env::consts::OS);
+    let release = format!(
+        r#"{{"tag_name": "v99.0.0", "assets": [
+            {{"name": "{name}", "browser_download_url": "{base}/{name}"}},
+            {{"name": "SHA256SUMS", "browser_download_url": "{base}/SHA256SUMS"}},
+            {{"name": "SHA256SUMS.minisig", "browser_download_url": "{base}/SHA256SUMS.minisig"}}
+        ]}}"#
+    );
+    let mut files: HashMap<String, Vec<u8>> = [
+        ("/release".to_owned(), release.into_bytes()),
+        (format!("/{name}"), binary.to_vec()),
+        ("/SHA256SUMS".to_owned(), sums.as_bytes().to_vec()),
+    ]
+    .into();
+    if let Some(signature) = signature {
+        files.insert("/SHA256SUMS.minisig".to_owned(), signature.into());
+    }
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let path = line.split(' ').nth(1).unwrap_or_default().to_owned();
+            // skip the headers
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let (status, body) = match files.get(&path) {
+                Some(body) => ("200 OK", body.as_slice()),
+                None => ("404 Not Found", &b""[..]),
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    format!("{base}/release")
+}
+
+/// A copy of fw of its own for the test called `name`, to be replaced.
+fn fw_copy(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fw-test-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let exe = dir.join("fw");
+    fs::copy(env!("CARGO_BIN_EXE_fw"), &exe).unwrap();
+    exe
+}
+
+fn self_update(exe: &Path, endpoint: &str, args: &[&str]) -> Output {
+    Command::new(exe)
+        .arg("self-update")
+        .args(["--endpoint", endpoint])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+const NEW_FW: &[u8] = b"#!/bin/sh\necho new fw\n";
+
+/// The SHA256SUMS of a release with [`NEW_FW`] for each platform that the tests might run on.
+const SUMS: &str = "\
+124f2a007bf5e9fe12e8e84025a75213b01712620421355459f23806ac7bb772  fw-aarch64-linux
+124f2a007bf5e9fe12e8e84025a75213b01712620421355459f23806ac7bb772  fw-x86_64-linux
+124f2a007bf5e9fe12e8e84025a75213b01712620421355459f23806ac7bb772  fw-aarch64-macos
+124f2a007bf5e9fe12e8e84025a75213b01712620421355459f23806ac7bb772  fw-x86_64-macos
+";
+
+/// The minisign public key that [`SIGNATURE`] is made with.
+const PUBLIC_KEY: &str = "RWQBAgMEBQYHCIqI4910CfGV/VLbLTy6XXLKZwm/HZQSG/N0iAG0D29c";
+
+/// [`SUMS`] signed by [`PUBLIC_KEY`].
+const SIGNATURE: &str = "\
+untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCHKOCgFbbriTH5EsX8OTNOKKrEgEEFn/f+pDhxJtrFqlRiY5QmNBAEAALM/KBZ5T9f6AiNC9RpNZ9TnK/K1kFgg=
+trusted comment: timestamp:1790000000\tfile:SHA256SUMS\thashed
+JBvhn0eAqwT9f731NTrsHLGWLNUTZbMsvVh27XS6CZ+VsukwoAdYD6IwnS4d2D/i51dvhhCG1uD7F8K7pQyuDA==
+";
+
+/// [`SUMS`] signed by some other key.
+const OTHER_SIGNATURE: &str = "\
+untrusted comment: signature from minisign secret key
+RUQLDA0ODxAREsZt+KCpdlW/8qjrmWirYkV/j7eIj4oA5oJQrvfl2YpmUIUSlusrpLeL/wpBMLGbX+ksYRY1kpdmpxwARRlriwE=
+trusted comment: timestamp:1790000000\tfile:SHA256SUMS\thashed
+rk0uJX6OrTjS6DKcK8giMZbzRe2cbQz2Uuthw2N7vsSyuyri9jYJa/FVZG+uhPvmAQOJPBRSBGpsVYen1AJKDA==
+";
+
+/// Check that the fw at `exe` hasn't been replaced and still works.
+fn assert_not_replaced(exe: &Path) {
+    let output = Command::new(exe)
+        .args(["--no-clipboard", "hi"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "ｈｉ\n");
+}
+
+#[test]
+fn self_update_replaces_the_executable() {
+    let endpoint = serve_release(NEW_FW, SUMS, Some(SIGNATURE));
+    let exe = fw_copy("update");
+
+    let output = self_update(&exe, &endpoint, &["--check"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("fw 99.0.0 is available"));
+
+    let output = self_update(&exe, &endpoint, &["--public-key", PUBLIC_KEY]);
+    assert!(output.status.success(), "{output:?}");
+    let output = Command::new(&exe).output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "new fw\n");
+    fs::remove_dir_all(exe.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn self_update_needs_a_key_or_insecure_no_signature() {
+    let endpoint = serve_release(NEW_FW, SUMS, None);
+    let exe = fw_copy("no-key");
+
+    let output = self_update(&exe, &endpoint, &[]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("can't check the release's signature"),
+        "{output:?}"
+    );
+    assert_not_replaced(&exe);
+
+    let output = self_update(&exe, &endpoint, &["--insecure-no-signature"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("not checking the release's signature")
+    );
+    let output = Command::new(&exe).output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "new fw\n");
+    fs::remove_dir_all(exe.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn self_update_checks_the_signature() {
+    let exe = fw_copy("bad-signature");
+    let tampered = SUMS.replacen("124f", "0000", 1);
+    for (sums, signature) in [(SUMS, OTHER_SIGNATURE), (&tampered, SIGNATURE)] {
+        let endpoint = serve_release(NEW_FW, sums, Some(signature));
+        let output = self_update(&exe, &endpoint, &["--public-key", PUBLIC_KEY]);
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr)
+                .contains("the release's checksums aren't signed by the public key"),
+            "{output:?}"
+        );
+        assert_not_replaced(&exe);
+    }
+    fs::remove_dir_all(exe.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn self_update_checks_the_checksum() {
+    let sums = SUMS.replace(&SUMS[..64], &"0".repeat(64));
+    let endpoint = serve_release(NEW_FW, &sums, None);
+    let exe = fw_copy("bad-sum");
+    let output = self_update(&exe, &endpoint, &["--insecure-no-signature"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("doesn't match its checksum"),
+        "{output:?}"
+    );
+    // the old fw is still there and working
+    assert_not_replaced(&exe);
+    fs::remove_dir_all(exe.parent().unwrap()).unwrap();
+}
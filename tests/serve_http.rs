@@ -0,0 +1,109 @@
+//! `fw serve-http` answers with JSON.
+#![cfg(feature = "http")]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, ChildStderr, Command, Stdio};
+
+/// A server on a port of its own, killed when dropped.
+struct Server {
+    child: Child,
+    stderr: BufReader<ChildStderr>,
+    addr: String,
+}
+
+impl Server {
+    fn start(config: &Path) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_fw"))
+            .args(["serve-http", "--listen", "127.0.0.1:0", "--config"])
+            .arg(config)
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let addr = line.trim().rsplit("http://").next().unwrap().to_owned();
+        Self {
+            child,
+            stderr,
+            addr,
+        }
+    }
+
+    /// Send the server `signal` and wait for it to say something about it.
+    fn signal(&mut self, signal: &str) -> String {
+        let status = Command::new("kill")
+            .arg(format!("-{signal}"))
+            .arg(self.child.id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let mut line = String::new();
+        self.stderr.read_line(&mut line).unwrap();
+        line
+    }
+
+    /// Send a request and return the status line and body.
+    fn request(&self, method: &str, path: &str, body: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(&self.addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: fw\r\nConnection: close\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.lines().next().unwrap().to_owned();
+        (status, body.to_owned())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn serve_http_converts() {
+    let server = Server::start(Path::new("/dev/null"));
+    let (status, body) = server.request("POST", "/convert?style=spaced", "hi there");
+    assert!(status.contains("200"), "{status}");
+    assert_eq!(body, r#"{"style":"spaced","text":"h i   t h e r e"}"#);
+
+    let (_, body) = server.request("POST", "/convert?decode=true", "ｈｉ");
+    assert_eq!(body, r#"{"style":"fullwidth","text":"hi"}"#);
+
+    let (status, body) = server.request("POST", "/convert?style=nope", "hi");
+    assert!(status.contains("400"), "{status}");
+    assert!(
+        body.starts_with(r#"{"error":"unknown style 'nope'"#),
+        "{body}"
+    );
+
+    let (status, body) = server.request("GET", "/styles", "");
+    assert!(status.contains("200"), "{status}");
+    assert!(body.contains(r#""name":"fullwidth""#), "{body}");
+}
+
+#[test]
+fn serve_http_reloads_the_config_on_sighup() {
+    let config = std::env::temp_dir().join(format!("fw-test-{}-serve.toml", std::process::id()));
+    fs::write(&config, "").unwrap();
+    let mut server = Server::start(&config);
+    let (status, _) = server.request("POST", "/convert?style=meme", "hi");
+    assert!(status.contains("400"), "{status}");
+
+    fs::write(&config, "[styles.meme]\nchain = [\"spaced\"]\n").unwrap();
+    assert!(server.signal("HUP").contains("reloaded the config file"));
+    let (_, body) = server.request("POST", "/convert?style=meme", "hi");
+    assert_eq!(body, r#"{"style":"meme","text":"h i"}"#);
+    fs::remove_file(&config).unwrap();
+}
@@ -0,0 +1,64 @@
+//! Reading stdin: `--stdin-timeout` gives up on a stdin that never delivers anything, and
+//! `--tee` passes what was read on along with its conversion.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn fw(args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_fw"));
+    cmd.arg("--no-clipboard")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cmd
+}
+
+#[test]
+fn stdin_timeout_fails_on_a_silent_stdin() {
+    let mut child = fw(&["--stdin-timeout", "1"]).spawn().unwrap();
+    // keep our end of the pipe open, like a launcher that never writes to it
+    let stdin = child.stdin.take().unwrap();
+    let output = child.wait_with_output().unwrap();
+    drop(stdin);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nothing on stdin after 1s"));
+}
+
+#[test]
+fn stdin_timeout_reads_input_that_arrives() {
+    let mut child = fw(&["--stdin-timeout", "5"]).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(b"hi\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "ｈｉ\n");
+
+    // arguments don't need stdin at all
+    let output = fw(&["--stdin-timeout", "0", "hi"]).output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "ｈｉ\n");
+}
+
+#[test]
+fn tee_prints_the_original_too() {
+    let mut child = fw(&["--tee"]).spawn().unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"a {out}\nb\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a {out}\nb\nａ　｛ｏｕｔ｝\nｂ\n"
+    );
+
+    let output = fw(&["--tee={orig} -> {out}", "--each", "a", "b"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a -> ａ\nb -> ｂ\n"
+    );
+}